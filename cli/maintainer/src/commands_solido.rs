@@ -1,12 +1,14 @@
 // SPDX-FileCopyrightText: 2021 Chorus One AG
 // SPDX-License-Identifier: GPL-3.0
 
-use std::{fmt, path::PathBuf};
+use std::{collections::BTreeSet, fmt, path::PathBuf};
 
 use serde::Serialize;
-use solana_program::{pubkey::Pubkey, system_instruction};
+use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use solana_program::{instruction::Instruction, pubkey::Pubkey, stake, system_instruction};
 use solana_sdk::{
     account::ReadableAccount,
+    message::Message,
     signature::{Keypair, Signer},
 };
 
@@ -16,17 +18,17 @@ use lido::{
     metrics::LamportsHistogram,
     processor::StakeType,
     state::{
-        AccountList, Criteria, Lido, ListEntry, Maintainer, RewardDistribution, SeedRange,
-        Validator, ValidatorPerf,
+        maintainer, AccountList, Criteria, Lido, ListEntry, Maintainer, RewardDistribution,
+        SeedRange, Validator, ValidatorPerf, ValidatorStatus,
     },
     token::{Lamports, StLamports},
-    util::serialize_b58,
+    util::{serialize_b58, serialize_b58_opt},
     vote_state::get_vote_account_commission,
     MINT_AUTHORITY, RESERVE_ACCOUNT, STAKE_AUTHORITY,
 };
 use solido_cli_common::{
     error::{CliError, Error},
-    per64::to_f64,
+    per64::{per64, to_f64},
     snapshot::{SnapshotClientConfig, SnapshotConfig},
     validator_info_utils::ValidatorInfo,
 };
@@ -40,9 +42,14 @@ use crate::{
 };
 use crate::{
     config::{
-        AddRemoveMaintainerOpts, AddValidatorOpts, ChangeCriteriaOpts, CreateSolidoOpts,
-        CreateV2AccountsOpts, DeactivateIfViolatesOpts, DeactivateValidatorOpts, DepositOpts,
-        MigrateStateToV2Opts, ShowSolidoAuthoritiesOpts, ShowSolidoOpts, WithdrawOpts,
+        AddRemoveMaintainerOpts, AddValidatorOpts, ChangeCriteriaOpts, ClaimVestedOpts,
+        CollectValidatorPerformanceOpts, CreateSolidoOpts, CreateV2AccountsOpts,
+        DeactivateIfViolatesOpts, DeactivateValidatorOpts, DecommissionValidatorOpts, DepositOpts,
+        DepositStakeOpts, DepositWithLockupOpts, FindOrphanedStakeAccountsOpts,
+        MigrateStateToV2Opts, PauseOpts, RebalanceStakeOpts, ReconcileStakeAccountsOpts,
+        RedelegateOpts, ResumeOpts, SetSolidoMetadataOpts, ShowSolidoAuthoritiesOpts,
+        ShowSolidoOpts, UpdateBalancesBatchOpts, UpdateBalancesOpts, VerifyMigrationOpts,
+        WithdrawOpts,
     },
     get_signer_from_path,
 };
@@ -84,6 +91,12 @@ pub struct CreateSolidoOutput {
     /// Data account that holds list of maintainers
     #[serde(serialize_with = "serialize_b58")]
     pub maintainer_list_address: Pubkey,
+
+    /// Metaplex metadata account for the stSOL mint, if `--token-name`,
+    /// `--token-symbol`, and `--token-uri` were provided and no existing
+    /// `--mint-address` was reused.
+    #[serde(serialize_with = "serialize_b58_opt")]
+    pub metadata_address: Option<Pubkey>,
 }
 
 impl fmt::Display for CreateSolidoOutput {
@@ -119,8 +132,139 @@ impl fmt::Display for CreateSolidoOutput {
             "  Developer fee SPL token account: {}",
             self.developer_account
         )?;
+        match self.metadata_address {
+            Some(metadata_address) => writeln!(
+                f,
+                "  Token metadata account:        {}",
+                metadata_address
+            )?,
+            None => writeln!(f, "  Token metadata account:        not created")?,
+        }
+        Ok(())
+    }
+}
+
+/// Push a Metaplex token metadata account creation instruction for `mint`
+/// onto `instructions`, so it can be batched into the same transaction that
+/// initializes the mint. Returns the derived metadata account address.
+fn push_create_metadata_instruction(
+    instructions: &mut Vec<solana_program::instruction::Instruction>,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Pubkey {
+    let (metadata_address, _) = mpl_token_metadata::pda::find_metadata_account(mint);
+    instructions.push(mpl_token_metadata::instruction::create_metadata_accounts_v3(
+        mpl_token_metadata::id(),
+        metadata_address,
+        *mint,
+        *mint_authority,
+        *payer,
+        *mint_authority,
+        name,
+        symbol,
+        uri,
+        None,
+        0,
+        false,
+        true,
+        None,
+        None,
+        None,
+    ));
+    metadata_address
+}
+
+/// A transaction that `send_or_dump_transaction` serialized instead of
+/// sending, because `SnapshotConfig::dry_run` was set.
+#[derive(Serialize)]
+pub struct DryRunTransaction {
+    /// A short label identifying which step of the command this is, so a
+    /// multi-transaction command like `command_create_solido` can be
+    /// reviewed step by step, in order.
+    pub label: String,
+
+    /// Base64-encoded transaction message, ready to be fed into an offline
+    /// multisig signing flow.
+    pub message_base64: String,
+
+    /// Pubkeys that still need to sign this transaction.
+    pub required_signers: Vec<String>,
+}
+
+/// Either send `instructions` right away, or, if `config.dry_run` is set,
+/// serialize them as a `DryRunTransaction` and print that instead.
+///
+/// This lets an operator review exactly what a multi-transaction command
+/// like `command_create_solido` would do, without broadcasting anything, by
+/// printing one `DryRunTransaction` per step, in the same order the
+/// transactions would otherwise have been sent in.
+fn send_or_dump_transaction(
+    config: &mut SnapshotConfig,
+    label: &str,
+    instructions: &[Instruction],
+    signers: &[&dyn Signer],
+) -> solido_cli_common::Result<()> {
+    if config.dry_run {
+        let message = Message::new(instructions, Some(&config.signer.pubkey()));
+        let message_base64 = base64::encode(message.serialize());
+        let required_signers = signers.iter().map(|s| s.pubkey().to_string()).collect();
+        let dump = DryRunTransaction {
+            label: label.to_string(),
+            message_base64,
+            required_signers,
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&dump)
+                .expect("DryRunTransaction always serializes to JSON.")
+        );
+        eprintln!("[dry-run] Would send: {}", label);
         Ok(())
+    } else {
+        config.sign_and_send_transaction(instructions, signers)?;
+        eprintln!("Did send: {}", label);
+        Ok(())
+    }
+}
+
+/// Multisig counterpart to `send_or_dump_transaction`: either propose
+/// `instruction` to the multisig right away, or, if `config.dry_run` is set,
+/// serialize it as a `DryRunTransaction` and print that instead.
+///
+/// This lets an operator diff a proposed multisig instruction, or feed it
+/// into an offline multisig signing flow, before anything is actually
+/// proposed. There is no `ProposeInstructionOutput` to report in that case,
+/// since no proposal was actually created, hence the `Option`.
+fn propose_or_dump_instruction(
+    config: &mut SnapshotConfig,
+    label: &str,
+    multisig_program_id: &Pubkey,
+    multisig_address: Pubkey,
+    instruction: Instruction,
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
+    if config.dry_run {
+        let message = Message::new(&[instruction], Some(&config.signer.pubkey()));
+        let message_base64 = base64::encode(message.serialize());
+        let dump = DryRunTransaction {
+            label: label.to_string(),
+            message_base64,
+            required_signers: vec![config.signer.pubkey().to_string()],
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&dump)
+                .expect("DryRunTransaction always serializes to JSON.")
+        );
+        eprintln!("[dry-run] Would propose: {}", label);
+        return Ok(None);
     }
+    let output = propose_instruction(config, multisig_program_id, multisig_address, instruction)?;
+    eprintln!("Did propose: {}", label);
+    Ok(Some(output))
 }
 
 /// Get keypair from key path or random if not set
@@ -193,6 +337,7 @@ pub fn command_create_solido(
         min_balance_empty_data_account.0,
     ));
 
+    let mut metadata_address = None;
     let st_sol_mint_pubkey = {
         if opts.mint_address() != &Pubkey::default() {
             // If we've been given a minter address, return its public key.
@@ -201,14 +346,31 @@ pub fn command_create_solido(
             // If not, set up the Lido stSOL SPL token mint account.
             let st_sol_mint_keypair =
                 push_create_spl_token_mint(config, &mut instructions, &mint_authority)?;
-            let signers = &[&st_sol_mint_keypair, config.signer];
+
+            // If the caller provided token metadata, create it in the same
+            // transaction batch as the mint, so wallets and explorers show a
+            // proper name and symbol instead of a bare mint address.
+            if let (Some(name), Some(symbol), Some(uri)) =
+                (opts.token_name(), opts.token_symbol(), opts.token_uri())
+            {
+                metadata_address = Some(push_create_metadata_instruction(
+                    &mut instructions,
+                    &config.signer.pubkey(),
+                    &st_sol_mint_keypair.pubkey(),
+                    &mint_authority,
+                    name.clone(),
+                    symbol.clone(),
+                    uri.clone(),
+                ));
+            }
+
+            let signers: &[&dyn Signer] = &[&st_sol_mint_keypair, config.signer];
             // Ideally we would set up the entire instance in a single transaction, but
             // Solana transaction size limits are so low that we need to break our
             // instructions down into multiple transactions. So set up the mint first,
             // then continue.
-            config.sign_and_send_transaction(&instructions[..], signers)?;
+            send_or_dump_transaction(config, "mint init", &instructions[..], signers)?;
             instructions.clear();
-            eprintln!("Did send mint init.");
             st_sol_mint_keypair.pubkey()
         }
     };
@@ -226,12 +388,13 @@ pub fn command_create_solido(
         &st_sol_mint_pubkey,
         opts.developer_account_owner(),
     )?;
-    config.sign_and_send_transaction(
+    send_or_dump_transaction(
+        config,
+        "SPL account inits",
         &instructions[..],
-        &vec![config.signer, &treasury_keypair, &developer_keypair],
+        &[config.signer, &treasury_keypair, &developer_keypair],
     )?;
     instructions.clear();
-    eprintln!("Did send SPL account inits.");
 
     // Create the account that holds the Solido instance itself.
     instructions.push(system_instruction::create_account(
@@ -280,6 +443,7 @@ pub fn command_create_solido(
             max_commission: *opts.max_commission(),
             min_block_production_rate: *opts.min_block_production_rate(),
             min_vote_success_rate: *opts.min_vote_success_rate(),
+            ..Criteria::default()
         },
         *opts.max_validators(),
         *opts.max_maintainers(),
@@ -296,9 +460,11 @@ pub fn command_create_solido(
         },
     ));
 
-    config.sign_and_send_transaction(
+    send_or_dump_transaction(
+        config,
+        "Lido init",
         &instructions[..],
-        &vec![
+        &[
             config.signer,
             &*lido_signer,
             &*validator_list_signer,
@@ -306,7 +472,6 @@ pub fn command_create_solido(
             &*maintainer_list_signer,
         ],
     )?;
-    eprintln!("Did send Lido init.");
 
     let result = CreateSolidoOutput {
         solido_address: lido_signer.pubkey(),
@@ -318,6 +483,7 @@ pub fn command_create_solido(
         validator_list_address: validator_list_signer.pubkey(),
         validator_perf_list_address: validator_perf_list_signer.pubkey(),
         maintainer_list_address: maintainer_list_signer.pubkey(),
+        metadata_address,
     };
     Ok(result)
 }
@@ -326,7 +492,7 @@ pub fn command_create_solido(
 pub fn command_add_validator(
     config: &mut SnapshotConfig,
     opts: &AddValidatorOpts,
-) -> solido_cli_common::Result<ProposeInstructionOutput> {
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
     let (multisig_address, _) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
 
@@ -341,8 +507,9 @@ pub fn command_add_validator(
             validator_list: solido.validator_list,
         },
     );
-    propose_instruction(
+    propose_or_dump_instruction(
         config,
+        "add validator",
         opts.multisig_program_id(),
         *opts.multisig_address(),
         instruction,
@@ -353,7 +520,7 @@ pub fn command_add_validator(
 pub fn command_deactivate_validator(
     config: &mut SnapshotConfig,
     opts: &DeactivateValidatorOpts,
-) -> solido_cli_common::Result<ProposeInstructionOutput> {
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
     let (multisig_address, _) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
 
@@ -376,8 +543,9 @@ pub fn command_deactivate_validator(
         },
         validator_index,
     );
-    propose_instruction(
+    propose_or_dump_instruction(
         config,
+        "deactivate validator",
         opts.multisig_program_id(),
         *opts.multisig_address(),
         instruction,
@@ -388,7 +556,7 @@ pub fn command_deactivate_validator(
 pub fn command_add_maintainer(
     config: &mut SnapshotConfig,
     opts: &AddRemoveMaintainerOpts,
-) -> solido_cli_common::Result<ProposeInstructionOutput> {
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
     let (multisig_address, _) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
 
@@ -403,8 +571,9 @@ pub fn command_add_maintainer(
             maintainer_list: solido.maintainer_list,
         },
     );
-    propose_instruction(
+    propose_or_dump_instruction(
         config,
+        "add maintainer",
         opts.multisig_program_id(),
         *opts.multisig_address(),
         instruction,
@@ -415,7 +584,7 @@ pub fn command_add_maintainer(
 pub fn command_remove_maintainer(
     config: &mut SnapshotConfig,
     opts: &AddRemoveMaintainerOpts,
-) -> solido_cli_common::Result<ProposeInstructionOutput> {
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
     let (multisig_address, _) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
 
@@ -438,8 +607,9 @@ pub fn command_remove_maintainer(
         },
         maintainer_index,
     );
-    propose_instruction(
+    propose_or_dump_instruction(
         config,
+        "remove maintainer",
         opts.multisig_program_id(),
         *opts.multisig_address(),
         instruction,
@@ -466,6 +636,10 @@ pub struct RichValidator {
     pub perf: Option<ValidatorPerf>,
 
     pub commission: u8,
+
+    /// Blended quality score, see `ValidatorPerf::score`. `None` if no
+    /// performance record exists yet for this validator.
+    pub score: Option<u32>,
 }
 
 #[derive(Serialize)]
@@ -691,6 +865,7 @@ impl fmt::Display for ShowSolidoOutput {
                 Vote account:              {}\n    \
                 Identity account:          {}\n    \
                 Commission:                {}%\n    \
+                Quality score:             {}\n    \
                 Active:                    {}\n    \
                 Stake in all accounts:     {}\n    \
                 Stake in stake accounts:   {}\n    \
@@ -703,6 +878,10 @@ impl fmt::Display for ShowSolidoOutput {
                 v.vote_account_address,
                 v.identity_account_address,
                 v.commission,
+                match v.score {
+                    Some(score) => format!("{}/100", score),
+                    None => "Not yet collected.".to_string(),
+                },
                 v.active,
                 v.stake_accounts_balance,
                 v.effective_stake_balance,
@@ -845,10 +1024,9 @@ pub fn command_show_solido(
         // account list, and it is written down in "first come, first serve" order.
         // But here in the CLI, we join the two lists by validator pubkey,
         // so that the two lists have the same indices.
-        let perf = available_perfs
-            .entries
-            .iter()
-            .find(|perf| &perf.validator_vote_account_address == validator.pubkey());
+        let perf = maintainer::find(&available_perfs.entries, |perf: &ValidatorPerf| {
+            &perf.validator_vote_account_address == validator.pubkey()
+        });
         validator_perfs.push(perf.cloned());
     }
     let validators = validators
@@ -867,6 +1045,7 @@ pub fn command_show_solido(
                 unstake_accounts_balance: v.unstake_accounts_balance,
                 effective_stake_balance: v.effective_stake_balance,
                 identity_account_address: identity,
+                score: perf.as_ref().map(|perf| perf.score(&lido.criteria)),
                 info,
                 perf,
                 commission,
@@ -889,217 +1068,1461 @@ pub fn command_show_solido(
     })
 }
 
+/// A stake account that is controlled by Solido's stake authority, but that
+/// is not (or no longer) predicted by any validator's tracked seed range; or,
+/// with a balance of zero, a predicted address that has no stake account
+/// under Solido's stake authority on chain.
 #[derive(Serialize)]
-pub struct ShowSolidoAuthoritiesOutput {
-    #[serde(serialize_with = "serialize_b58")]
-    pub solido_program_id: Pubkey,
-
-    #[serde(serialize_with = "serialize_b58")]
-    pub solido_address: Pubkey,
-
+pub struct OrphanedStakeAccount {
     #[serde(serialize_with = "serialize_b58")]
-    pub reserve_account: Pubkey,
-
-    #[serde(serialize_with = "serialize_b58")]
-    pub stake_authority: Pubkey,
+    pub address: Pubkey,
+    pub balance: Lamports,
+}
 
-    #[serde(serialize_with = "serialize_b58")]
-    pub mint_authority: Pubkey,
+#[derive(Serialize)]
+pub struct FindOrphanedStakeAccountsOutput {
+    pub orphaned_accounts: Vec<OrphanedStakeAccount>,
+    pub total_orphaned_balance: Lamports,
 }
 
-impl fmt::Display for ShowSolidoAuthoritiesOutput {
+impl fmt::Display for FindOrphanedStakeAccountsOutput {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "Stake authority:            {}", self.stake_authority,)?;
-        writeln!(f, "Mint authority:             {}", self.mint_authority)?;
-        writeln!(f, "Reserve account:            {}", self.reserve_account)?;
+        if self.orphaned_accounts.is_empty() {
+            return writeln!(f, "No orphaned stake accounts found.");
+        }
+        writeln!(f, "Orphaned stake accounts (address, balance):")?;
+        for account in &self.orphaned_accounts {
+            writeln!(f, "  - {}: {}", account.address, account.balance)?;
+        }
+        writeln!(
+            f,
+            "\nTotal stranded balance: {}",
+            self.total_orphaned_balance
+        )?;
         Ok(())
     }
 }
 
-pub fn command_show_solido_authorities(
-    opts: &ShowSolidoAuthoritiesOpts,
-) -> solido_cli_common::Result<ShowSolidoAuthoritiesOutput> {
-    let (reserve_account, _) = find_authority_program_address(
-        opts.solido_program_id(),
-        opts.solido_address(),
-        RESERVE_ACCOUNT,
-    );
-    let (mint_authority, _) = find_authority_program_address(
-        opts.solido_program_id(),
-        opts.solido_address(),
-        MINT_AUTHORITY,
-    );
-    let (stake_authority, _) = find_authority_program_address(
+/// Fetch every stake account Solido's stake authority could plausibly
+/// control, together with the set of addresses the validator list's seed
+/// ranges predict.
+///
+/// Shared by `command_find_orphaned_stake_accounts` and
+/// `command_reconcile_stake_accounts`, which both start from the same
+/// `getProgramAccounts` scan and only differ in what they report about it.
+fn scan_stake_accounts_by_authority(
+    config: &mut SnapshotConfig,
+    opts_solido_program_id: &Pubkey,
+    opts_solido_address: &Pubkey,
+) -> solido_cli_common::Result<(Vec<(Pubkey, solana_sdk::account::Account)>, BTreeSet<Pubkey>)> {
+    let lido = config.client.get_solido(opts_solido_address)?;
+    let stake_authority =
+        lido.get_stake_authority(opts_solido_program_id, opts_solido_address)?;
+
+    // The stake program stores the withdraw authority at byte offset 44 of
+    // `StakeState`, so a `Memcmp` filter there finds every stake account that
+    // Solido could plausibly control, without first walking the validator
+    // list's seed ranges.
+    let accounts_by_authority = config.client.get_program_accounts(
+        &stake::program::id(),
+        vec![RpcFilterType::Memcmp(Memcmp {
+            offset: 44,
+            bytes: MemcmpEncodedBytes::Bytes(stake_authority.to_bytes().to_vec()),
+            encoding: None,
+        })],
+    )?;
+
+    let validators = config
+        .client
+        .get_account_list::<Validator>(&lido.validator_list)?
+        .entries;
+
+    // Every address Solido itself could have derived, across both the
+    // staking and unstaking seed ranges of every tracked validator.
+    let mut predicted_addresses = BTreeSet::new();
+    for validator in &validators {
+        for seed in &validator.stake_seeds {
+            let (address, _) = find_stake_account_address(
+                validator.pubkey(),
+                opts_solido_program_id,
+                opts_solido_address,
+                seed,
+                StakeType::Stake,
+            );
+            predicted_addresses.insert(address);
+        }
+        for seed in &validator.unstake_seeds {
+            let (address, _) = find_stake_account_address(
+                validator.pubkey(),
+                opts_solido_program_id,
+                opts_solido_address,
+                seed,
+                StakeType::Unstake,
+            );
+            predicted_addresses.insert(address);
+        }
+    }
+
+    Ok((accounts_by_authority, predicted_addresses))
+}
+
+/// CLI entry point to find stake accounts that Solido's stake authority
+/// controls, but that are not tracked by any validator's seed range.
+///
+/// This can happen if a stake account is created directly against the stake
+/// authority PDA outside of Solido's instructions, or if a validator is
+/// removed while it still has stake accounts derived from seeds that are no
+/// longer reachable through `find_stake_account_address`. Predicted addresses
+/// that have no stake account on chain are reported too, with a zero balance.
+pub fn command_find_orphaned_stake_accounts(
+    config: &mut SnapshotConfig,
+    opts: &FindOrphanedStakeAccountsOpts,
+) -> solido_cli_common::Result<FindOrphanedStakeAccountsOutput> {
+    let (accounts_by_authority, predicted_addresses) = scan_stake_accounts_by_authority(
+        config,
         opts.solido_program_id(),
         opts.solido_address(),
-        STAKE_AUTHORITY,
-    );
-    Ok(ShowSolidoAuthoritiesOutput {
-        solido_program_id: *opts.solido_program_id(),
-        solido_address: *opts.solido_address(),
-        reserve_account,
-        stake_authority,
-        mint_authority,
+    )?;
+    let onchain_addresses: BTreeSet<Pubkey> =
+        accounts_by_authority.iter().map(|(address, _)| *address).collect();
+
+    let mut orphaned_accounts = Vec::new();
+    let mut total_orphaned_balance = Lamports(0);
+
+    // On-chain accounts that no validator's seed range predicts: these hold
+    // stranded lamports that a maintainer can reclaim.
+    for (address, account) in &accounts_by_authority {
+        if !predicted_addresses.contains(address) {
+            let balance = Lamports(account.lamports());
+            total_orphaned_balance = Lamports(total_orphaned_balance.0 + balance.0);
+            orphaned_accounts.push(OrphanedStakeAccount {
+                address: *address,
+                balance,
+            });
+        }
+    }
+    // Predicted addresses that are not on chain: these are seeds the program
+    // still tracks, but that do not correspond to an actual stake account
+    // anymore, so there is nothing to reclaim, but it is still worth flagging.
+    for address in &predicted_addresses {
+        if !onchain_addresses.contains(address) {
+            orphaned_accounts.push(OrphanedStakeAccount {
+                address: *address,
+                balance: Lamports(0),
+            });
+        }
+    }
+
+    Ok(FindOrphanedStakeAccountsOutput {
+        orphaned_accounts,
+        total_orphaned_balance,
     })
 }
 
+/// A stake account that Solido's stake authority controls, but that is not
+/// accounted for by any validator's tracked seed range; or, with a balance of
+/// zero, a seed-predicted address that has no such stake account on chain.
 #[derive(Serialize)]
-pub struct DepositOutput {
+pub struct UnaccountedStakeAccount {
     #[serde(serialize_with = "serialize_b58")]
-    pub recipient: Pubkey,
-
-    /// Amount of stSOL we expected to receive based on the exchange rate at the time of the deposit.
-    ///
-    /// This can differ from the actual amount, when a deposit happens close to
-    /// an epoch boundary, and an `UpdateExchangeRate` instruction executed before
-    /// our deposit, but after we checked the exchange rate.
-    #[serde(rename = "expected_st_lamports")]
-    pub expected_st_sol: StLamports,
-
-    /// The difference in stSOL balance before and after our deposit.
-    ///
-    /// If no other transactions touch the recipient account, then this is the
-    /// amount of stSOL we got. However, the stSOL account balance might change
-    /// for other reasons than just the deposit, if another transaction touched
-    /// the account in the same block.
-    #[serde(rename = "st_lamports_balance_increase")]
-    pub st_sol_balance_increase: StLamports,
+    pub address: Pubkey,
+    pub balance: Lamports,
+}
 
-    /// Whether we had to create the associated stSOL account. False if one existed already.
-    pub created_associated_st_sol_account: bool,
+#[derive(Serialize)]
+pub struct ReconcileStakeAccountsOutput {
+    /// Sum of the lamports held by every stake account under Solido's stake authority, on chain.
+    pub total_onchain_balance: Lamports,
+
+    /// Sum of `stake_accounts_balance` and `unstake_accounts_balance` across
+    /// every validator in the validator list, as tracked by the program.
+    pub total_tracked_balance: Lamports,
+
+    /// On-chain stake accounts under Solido's authority that no validator's
+    /// seed range accounts for, followed by seed-predicted addresses that
+    /// have no such stake account on chain (listed with a zero balance).
+    pub unaccounted_accounts: Vec<UnaccountedStakeAccount>,
 }
 
-impl fmt::Display for DepositOutput {
+impl fmt::Display for ReconcileStakeAccountsOutput {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.created_associated_st_sol_account {
-            writeln!(f, "Created recipient stSOL account, it did not yet exist.")?;
-        } else {
-            writeln!(f, "Recipient stSOL account existed already before deposit.")?;
-        }
-        writeln!(f, "Recipient stSOL account: {}", self.recipient)?;
-        writeln!(f, "Expected stSOL amount:   {}", self.expected_st_sol)?;
         writeln!(
             f,
-            "stSOL balance increase:  {}",
-            self.st_sol_balance_increase
+            "Total balance on chain:  {}",
+            self.total_onchain_balance
+        )?;
+        writeln!(
+            f,
+            "Total balance tracked:   {}",
+            self.total_tracked_balance
         )?;
+        if self.total_onchain_balance == self.total_tracked_balance {
+            writeln!(f, "These amounts match.")?;
+        } else {
+            writeln!(f, "These amounts do NOT match!")?;
+        }
+        if self.unaccounted_accounts.is_empty() {
+            writeln!(f, "No unaccounted-for stake accounts found.")?;
+        } else {
+            writeln!(f, "Unaccounted-for stake accounts (address, balance):")?;
+            for account in &self.unaccounted_accounts {
+                writeln!(f, "  - {}: {}", account.address, account.balance)?;
+            }
+        }
         Ok(())
     }
 }
 
-pub fn command_deposit(
-    config: &mut SnapshotClientConfig,
-    opts: &DepositOpts,
-) -> std::result::Result<DepositOutput, Error> {
-    let (recipient, created_recipient) = config.with_snapshot(|config| {
-        let solido = config.client.get_solido(opts.solido_address())?;
-
-        let recipient = spl_associated_token_account::get_associated_token_address(
-            &config.signer.pubkey(),
-            &solido.st_sol_mint,
-        );
-
-        if !config.client.account_exists(&recipient)? {
-            let instr = spl_associated_token_account::create_associated_token_account(
-                &config.signer.pubkey(),
-                &config.signer.pubkey(),
-                &solido.st_sol_mint,
-            );
-
-            config.sign_and_send_transaction(&[instr], &[config.signer])?;
-
-            Ok((recipient, true))
-        } else {
-            Ok((recipient, false))
-        }
-    })?;
-
-    let (balance_before, exchange_rate) = config.with_snapshot(|config| {
-        let balance_before = config
-            .client
-            .get_spl_token_balance(&recipient)
-            .map(StLamports)?;
-        let solido = config.client.get_solido(opts.solido_address())?;
-        let reserve =
-            solido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
-        let mint_authority =
-            solido.get_mint_authority(opts.solido_program_id(), opts.solido_address())?;
-
-        let instr = lido::instruction::deposit(
-            opts.solido_program_id(),
-            &lido::instruction::DepositAccountsMeta {
-                lido: *opts.solido_address(),
-                user: config.signer.pubkey(),
-                recipient,
-                st_sol_mint: solido.st_sol_mint,
-                mint_authority,
-                reserve_account: reserve,
-            },
-            *opts.amount_sol(),
-        );
-
-        config.sign_and_send_transaction(&[instr], &[config.signer])?;
+/// CLI entry point to reconcile the real chain state against the balances
+/// the Solido program tracks in its validator list.
+///
+/// This runs the same `getProgramAccounts` scan as
+/// `command_find_orphaned_stake_accounts`, but instead of just listing
+/// stranded accounts, it sums up both sides of the ledger so a maintainer
+/// can confirm the program's tracked balances are not drifting from reality.
+pub fn command_reconcile_stake_accounts(
+    config: &mut SnapshotConfig,
+    opts: &ReconcileStakeAccountsOpts,
+) -> solido_cli_common::Result<ReconcileStakeAccountsOutput> {
+    let lido = config.client.get_solido(opts.solido_address())?;
+    let validators = config
+        .client
+        .get_account_list::<Validator>(&lido.validator_list)?
+        .entries;
+
+    let total_tracked_balance = validators.iter().fold(Lamports(0), |acc, validator| {
+        Lamports(
+            acc.0
+                + validator.stake_accounts_balance.0
+                + validator.unstake_accounts_balance.0,
+        )
+    });
 
-        Ok((balance_before, solido.exchange_rate))
-    })?;
+    let (accounts_by_authority, predicted_addresses) = scan_stake_accounts_by_authority(
+        config,
+        opts.solido_program_id(),
+        opts.solido_address(),
+    )?;
+    let onchain_addresses: BTreeSet<Pubkey> =
+        accounts_by_authority.iter().map(|(address, _)| *address).collect();
 
-    let balance_after = config.with_snapshot(|config| {
-        config
-            .client
-            .get_spl_token_balance(&recipient)
-            .map(StLamports)
-    })?;
+    let total_onchain_balance = accounts_by_authority
+        .iter()
+        .fold(Lamports(0), |acc, (_, account)| {
+            Lamports(acc.0 + account.lamports())
+        });
 
-    let st_sol_balance_increase = StLamports(balance_after.0.saturating_sub(balance_before.0));
-    let expected_st_sol = exchange_rate
-        .exchange_sol(*opts.amount_sol())
-        // If this is not an `Ok`, the transaction should have failed, but if
-        // the transaction did not fail, then we do want to show the output; we
-        // don't want the user to think that the deposit failed.
-        .unwrap_or(StLamports(0));
+    let unaccounted_accounts = accounts_by_authority
+        .iter()
+        .filter(|(address, _)| !predicted_addresses.contains(address))
+        .map(|(address, account)| UnaccountedStakeAccount {
+            address: *address,
+            balance: Lamports(account.lamports()),
+        })
+        .chain(
+            predicted_addresses
+                .iter()
+                .filter(|address| !onchain_addresses.contains(address))
+                .map(|address| UnaccountedStakeAccount {
+                    address: *address,
+                    balance: Lamports(0),
+                }),
+        )
+        .collect();
 
-    let result = DepositOutput {
-        recipient,
-        expected_st_sol,
-        st_sol_balance_increase,
-        created_associated_st_sol_account: created_recipient,
-    };
-    Ok(result)
+    Ok(ReconcileStakeAccountsOutput {
+        total_onchain_balance,
+        total_tracked_balance,
+        unaccounted_accounts,
+    })
 }
 
-#[derive(Serialize)]
-pub struct WithdrawOutput {
-    #[serde(serialize_with = "serialize_b58")]
-    pub from_token_address: Pubkey,
+/// Maximum number of validators whose stake-account balance we refresh in a
+/// single transaction. `command_create_solido` has to split its setup across
+/// several transactions for the same reason: Solana's 1232-byte packet limit
+/// does not leave room to update every validator at once.
+pub const MAX_ACCOUNTS_TO_UPDATE: usize = 10;
 
-    /// Amount of SOL that was withdrawn.
-    pub withdrawn_sol: Lamports,
+#[derive(Serialize)]
+pub struct UpdateBalancesOutput {
+    /// Number of validators whose stake-account balance was refreshed.
+    pub validators_updated: usize,
 
-    /// Newly created stake account, where the source stake account will be
-    /// split to.
-    #[serde(serialize_with = "serialize_b58")]
-    pub new_stake_account: Pubkey,
+    /// Number of transactions the updates were split across.
+    pub transactions_sent: usize,
 }
 
-impl fmt::Display for WithdrawOutput {
+impl fmt::Display for UpdateBalancesOutput {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "Withdrawn from:          {}", self.from_token_address)?;
-        writeln!(f, "Total SOL withdrawn:     {}", self.withdrawn_sol)?;
-        writeln!(f, "New stake account:       {}", self.new_stake_account)?;
-        Ok(())
+        writeln!(
+            f,
+            "Updated the stake-account balance of {} validator(s), across {} transaction(s).",
+            self.validators_updated, self.transactions_sent
+        )
     }
 }
 
-pub fn command_withdraw(
-    config: &mut SnapshotClientConfig,
-    opts: &WithdrawOpts,
-) -> std::result::Result<WithdrawOutput, Error> {
-    let (st_sol_address, new_stake_account) = config.with_snapshot(|config| {
-        let solido = config.client.get_solido(opts.solido_address())?;
-
-        let validators = config
-            .client
-            .get_account_list::<Validator>(&solido.validator_list)?;
+/// CLI entry point to refresh every validator's stake-account balance (and
+/// distribute the rewards this reveals), chunking the per-validator update
+/// instructions so that no single transaction exceeds Solana's packet
+/// size limit.
+pub fn command_update_balances(
+    config: &mut SnapshotConfig,
+    opts: &UpdateBalancesOpts,
+) -> solido_cli_common::Result<UpdateBalancesOutput> {
+    let lido = config.client.get_solido(opts.solido_address())?;
+    let stake_authority =
+        lido.get_stake_authority(opts.solido_program_id(), opts.solido_address())?;
+    let mint_authority =
+        lido.get_mint_authority(opts.solido_program_id(), opts.solido_address())?;
+    let reserve_account =
+        lido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
+
+    let validators = config
+        .client
+        .get_account_list::<Validator>(&lido.validator_list)?
+        .entries;
+
+    let mut validators_updated = 0;
+    let mut transactions_sent = 0;
+
+    for chunk in validators.iter().enumerate().collect::<Vec<_>>().chunks(MAX_ACCOUNTS_TO_UPDATE) {
+        let mut instructions = Vec::new();
+        for (validator_index, validator) in chunk {
+            let mut stake_accounts = Vec::new();
+            for seed in &validator.stake_seeds {
+                stake_accounts.push(
+                    find_stake_account_address(
+                        validator.pubkey(),
+                        opts.solido_program_id(),
+                        opts.solido_address(),
+                        seed,
+                        StakeType::Stake,
+                    )
+                    .0,
+                );
+            }
+            for seed in &validator.unstake_seeds {
+                stake_accounts.push(
+                    find_stake_account_address(
+                        validator.pubkey(),
+                        opts.solido_program_id(),
+                        opts.solido_address(),
+                        seed,
+                        StakeType::Unstake,
+                    )
+                    .0,
+                );
+            }
+
+            instructions.push(lido::instruction::update_stake_account_balance(
+                opts.solido_program_id(),
+                &lido::instruction::UpdateStakeAccountBalanceMeta {
+                    lido: *opts.solido_address(),
+                    validator_vote_account: *validator.pubkey(),
+                    stake_accounts,
+                    reserve: reserve_account,
+                    stake_authority,
+                    st_sol_mint: lido.st_sol_mint,
+                    mint_authority,
+                    treasury_st_sol_account: lido.fee_recipients.treasury_account,
+                    developer_st_sol_account: lido.fee_recipients.developer_account,
+                    validator_list: lido.validator_list,
+                },
+                *validator_index as u32,
+            ));
+            validators_updated += 1;
+        }
+
+        send_or_dump_transaction(
+            config,
+            "update balances",
+            &instructions[..],
+            &[config.signer],
+        )?;
+        transactions_sent += 1;
+        eprintln!(
+            "Updated balances for {} validator(s) in transaction {}.",
+            chunk.len(),
+            transactions_sent
+        );
+    }
+
+    Ok(UpdateBalancesOutput {
+        validators_updated,
+        transactions_sent,
+    })
+}
+
+/// Largest slice of the validator list that a single
+/// `UpdateStakeAccountBalanceBatch` instruction can cover, chosen so the
+/// flattened stake/unstake accounts of the batch still fit within the
+/// transaction's compute and account limits.
+pub const MAX_VALIDATORS_PER_BALANCE_BATCH: usize = 10;
+
+/// CLI entry point to refresh a contiguous slice of the validator list's
+/// stake-account balances with a single `UpdateStakeAccountBalanceBatch`
+/// instruction per batch, instead of one instruction per validator.
+///
+/// This amortizes the shared accounts (reserve, mint, fee recipients,
+/// validator list) across every validator in the batch, the same way SPL
+/// stake-pool's `update_validator_list_balance` does. Validators enqueued
+/// for removal are skipped, since `remove_validator` requires them to end
+/// up with no stake accounts rather than a freshly observed balance.
+pub fn command_update_balances_batch(
+    config: &mut SnapshotConfig,
+    opts: &UpdateBalancesBatchOpts,
+) -> solido_cli_common::Result<UpdateBalancesOutput> {
+    let lido = config.client.get_solido(opts.solido_address())?;
+    let stake_authority =
+        lido.get_stake_authority(opts.solido_program_id(), opts.solido_address())?;
+    let mint_authority =
+        lido.get_mint_authority(opts.solido_program_id(), opts.solido_address())?;
+    let reserve_account =
+        lido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
+
+    let validators = config
+        .client
+        .get_account_list::<Validator>(&lido.validator_list)?
+        .entries;
+
+    let mut validators_updated = 0;
+    let mut transactions_sent = 0;
+
+    // `UpdateStakeAccountBalanceBatch` takes a contiguous `[start_index,
+    // start_index + count)` slice of the validator list, and its stake
+    // accounts must be passed in list order. Validators enqueued for
+    // removal are skipped, but we cannot just filter them out and then
+    // chunk the remainder: that would turn `count` into a span that still
+    // includes the skipped validator on-chain (since the processor walks
+    // the list by index) while dropping a trailing validator that should
+    // have been included, misattributing balances. Instead, batch only
+    // within maximal runs of consecutive non-`PendingRemoval` validators.
+    let mut run_start = 0;
+    while run_start < validators.len() {
+        if validators[run_start].status == ValidatorStatus::PendingRemoval {
+            run_start += 1;
+            continue;
+        }
+
+        let run_end = validators[run_start..]
+            .iter()
+            .position(|validator| validator.status == ValidatorStatus::PendingRemoval)
+            .map_or(validators.len(), |offset| run_start + offset);
+
+        let mut batch_start = run_start;
+        while batch_start < run_end {
+            let batch_end = (batch_start + MAX_VALIDATORS_PER_BALANCE_BATCH).min(run_end);
+            let batch = &validators[batch_start..batch_end];
+
+            let mut stake_accounts = Vec::new();
+            for validator in batch {
+                for seed in &validator.stake_seeds {
+                    stake_accounts.push(
+                        find_stake_account_address(
+                            validator.pubkey(),
+                            opts.solido_program_id(),
+                            opts.solido_address(),
+                            seed,
+                            StakeType::Stake,
+                        )
+                        .0,
+                    );
+                }
+                for seed in &validator.unstake_seeds {
+                    stake_accounts.push(
+                        find_stake_account_address(
+                            validator.pubkey(),
+                            opts.solido_program_id(),
+                            opts.solido_address(),
+                            seed,
+                            StakeType::Unstake,
+                        )
+                        .0,
+                    );
+                }
+            }
+
+            let instruction = lido::instruction::update_stake_account_balance_batch(
+                opts.solido_program_id(),
+                &lido::instruction::UpdateStakeAccountBalanceBatchMeta {
+                    lido: *opts.solido_address(),
+                    stake_accounts,
+                    reserve: reserve_account,
+                    stake_authority,
+                    st_sol_mint: lido.st_sol_mint,
+                    mint_authority,
+                    treasury_st_sol_account: lido.fee_recipients.treasury_account,
+                    developer_st_sol_account: lido.fee_recipients.developer_account,
+                    validator_list: lido.validator_list,
+                },
+                batch_start as u32,
+                batch.len() as u32,
+            );
+
+            send_or_dump_transaction(
+                config,
+                "update balances batch",
+                &[instruction],
+                &[config.signer],
+            )?;
+            transactions_sent += 1;
+            validators_updated += batch.len();
+            eprintln!(
+                "Updated balances for {} validator(s) in a single batch instruction, transaction {}.",
+                batch.len(),
+                transactions_sent
+            );
+
+            batch_start = batch_end;
+        }
+
+        run_start = run_end;
+    }
+
+    Ok(UpdateBalancesOutput {
+        validators_updated,
+        transactions_sent,
+    })
+}
+
+#[derive(Serialize)]
+pub struct DecommissionValidatorOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub validator_vote_account: Pubkey,
+
+    /// The fresh unstake accounts that now hold the validator's former stake.
+    pub unstake_accounts: Vec<Pubkey>,
+
+    /// Total amount moved out of the validator's stake accounts.
+    pub total_unstaked: Lamports,
+}
+
+impl fmt::Display for DecommissionValidatorOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Decommissioned validator: {}", self.validator_vote_account)?;
+        writeln!(f, "Total unstaked:           {}", self.total_unstaked)?;
+        writeln!(f, "New unstake account(s):")?;
+        for unstake_account in &self.unstake_accounts {
+            writeln!(f, "  - {}", unstake_account)?;
+        }
+        Ok(())
+    }
+}
+
+/// CLI entry point for the maintainer-run equivalent of SPL stake-pool's
+/// validator teardown: in one transaction, splits and deactivates every
+/// active stake account of the target validator into fresh unstake
+/// accounts, and enqueues the validator for removal.
+///
+/// This collapses what used to be a multi-step chore (unstake every stake
+/// account one at a time, wait for cooldown, update the balance, and only
+/// then remove the validator) into a single maintainer call; the normal
+/// balance-update cycle still has to run out the cooldown before
+/// `remove_validator` can succeed.
+pub fn command_decommission_validator(
+    config: &mut SnapshotConfig,
+    opts: &DecommissionValidatorOpts,
+) -> solido_cli_common::Result<DecommissionValidatorOutput> {
+    let lido = config.client.get_solido(opts.solido_address())?;
+    let validators = config
+        .client
+        .get_account_list::<Validator>(&lido.validator_list)?;
+    let maintainers = config
+        .client
+        .get_account_list::<Maintainer>(&lido.maintainer_list)?;
+
+    let validator = validators
+        .find(opts.validator_vote_account())
+        .ok_or_else(|| CliError::new("Pubkey not found in validator list"))?;
+    let validator_index = validators
+        .position(opts.validator_vote_account())
+        .ok_or_else(|| CliError::new("Pubkey not found in validator list"))?;
+    let maintainer_index = maintainers
+        .position(&config.signer.pubkey())
+        .ok_or_else(|| CliError::new("Signer is not a maintainer of this Solido instance"))?;
+
+    let stake_authority =
+        lido.get_stake_authority(opts.solido_program_id(), opts.solido_address())?;
+
+    let mut source_stake_accounts = Vec::new();
+    let mut total_unstaked = Lamports(0);
+    for seed in &validator.stake_seeds {
+        let (stake_account, _) = validator.find_stake_account_address(
+            opts.solido_program_id(),
+            opts.solido_address(),
+            seed,
+            StakeType::Stake,
+        );
+        total_unstaked =
+            Lamports(total_unstaked.0 + config.client.get_account(&stake_account)?.lamports());
+        source_stake_accounts.push(stake_account);
+    }
+
+    let destination_unstake_accounts: Vec<Pubkey> = (validator.unstake_seeds.end
+        ..validator.unstake_seeds.end + source_stake_accounts.len() as u64)
+        .map(|seed| {
+            validator
+                .find_stake_account_address(
+                    opts.solido_program_id(),
+                    opts.solido_address(),
+                    seed,
+                    StakeType::Unstake,
+                )
+                .0
+        })
+        .collect();
+
+    let instruction = lido::instruction::decommission_validator(
+        opts.solido_program_id(),
+        &lido::instruction::DecommissionValidatorAccountsMeta {
+            lido: *opts.solido_address(),
+            validator_vote_account: *validator.pubkey(),
+            source_stake_accounts,
+            destination_unstake_accounts: destination_unstake_accounts.clone(),
+            stake_authority,
+            maintainer: config.signer.pubkey(),
+            validator_list: lido.validator_list,
+            maintainer_list: lido.maintainer_list,
+        },
+        validator_index,
+        maintainer_index,
+    );
+    send_or_dump_transaction(
+        config,
+        "decommission validator",
+        &[instruction],
+        &[config.signer],
+    )?;
+
+    Ok(DecommissionValidatorOutput {
+        validator_vote_account: *validator.pubkey(),
+        unstake_accounts: destination_unstake_accounts,
+        total_unstaked,
+    })
+}
+
+#[derive(Serialize)]
+pub struct RebalanceStakeOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub from_vote_account: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    pub to_vote_account: Pubkey,
+
+    pub rebalanced_amount: Lamports,
+
+    #[serde(serialize_with = "serialize_b58")]
+    pub destination_unstake_account: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    pub destination_stake_account: Pubkey,
+}
+
+impl fmt::Display for RebalanceStakeOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "From validator:      {}", self.from_vote_account)?;
+        writeln!(f, "To validator:        {}", self.to_vote_account)?;
+        writeln!(f, "Rebalanced amount:   {}", self.rebalanced_amount)?;
+        writeln!(
+            f,
+            "Source unstake account:     {}",
+            self.destination_unstake_account
+        )?;
+        writeln!(
+            f,
+            "Destination stake account:  {}",
+            self.destination_stake_account
+        )?;
+        Ok(())
+    }
+}
+
+/// CLI entry point for the maintainer-driven cross-validator rebalance: in
+/// one transaction, decreases `from_vote_account`'s active stake by
+/// splitting `amount` into a fresh unstake account, and simultaneously
+/// increases `to_vote_account` by staking `amount` from the reserve into
+/// its append stake account.
+///
+/// This is the Solido analogue of SPL stake-pool's paired
+/// decrease/increase-validator-stake instructions: the decreasing leg
+/// parks SOL in a deactivating account that the balance-update cycle still
+/// counts, while the increasing leg draws from reserve liquidity, so the
+/// exchange rate stays correct throughout the cooldown.
+pub fn command_rebalance_stake(
+    config: &mut SnapshotConfig,
+    opts: &RebalanceStakeOpts,
+) -> solido_cli_common::Result<RebalanceStakeOutput> {
+    let lido = config.client.get_solido(opts.solido_address())?;
+    let validators = config
+        .client
+        .get_account_list::<Validator>(&lido.validator_list)?;
+    let maintainers = config
+        .client
+        .get_account_list::<Maintainer>(&lido.maintainer_list)?;
+
+    let from_validator = validators
+        .find(opts.from_vote_account())
+        .ok_or_else(|| CliError::new("Source validator is not part of this Solido instance."))?;
+    let to_validator = validators
+        .find(opts.to_vote_account())
+        .ok_or_else(|| CliError::new("Destination validator is not part of this Solido instance."))?;
+
+    let from_validator_index = validators
+        .position(opts.from_vote_account())
+        .ok_or_else(|| CliError::new("Pubkey not found in validator list"))?;
+    let to_validator_index = validators
+        .position(opts.to_vote_account())
+        .ok_or_else(|| CliError::new("Pubkey not found in validator list"))?;
+    let maintainer_index = maintainers
+        .position(&config.signer.pubkey())
+        .ok_or_else(|| CliError::new("Signer is not a maintainer of this Solido instance"))?;
+
+    let stake_authority =
+        lido.get_stake_authority(opts.solido_program_id(), opts.solido_address())?;
+    let reserve_account =
+        lido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
+
+    let (source_stake_account, _) = from_validator.find_stake_account_address(
+        opts.solido_program_id(),
+        opts.solido_address(),
+        from_validator.stake_seeds.begin,
+        StakeType::Stake,
+    );
+    let (destination_unstake_account, _) = from_validator.find_stake_account_address(
+        opts.solido_program_id(),
+        opts.solido_address(),
+        from_validator.unstake_seeds.end,
+        StakeType::Unstake,
+    );
+    let (destination_stake_account, _) = to_validator.find_stake_account_address(
+        opts.solido_program_id(),
+        opts.solido_address(),
+        to_validator.stake_seeds.end,
+        StakeType::Stake,
+    );
+
+    let instruction = lido::instruction::rebalance_stake(
+        opts.solido_program_id(),
+        &lido::instruction::RebalanceStakeAccountsMeta {
+            lido: *opts.solido_address(),
+            from_validator_vote_account: *from_validator.pubkey(),
+            to_validator_vote_account: *to_validator.pubkey(),
+            source_stake_account,
+            destination_unstake_account,
+            reserve: reserve_account,
+            destination_stake_account,
+            stake_authority,
+            maintainer: config.signer.pubkey(),
+            validator_list: lido.validator_list,
+            maintainer_list: lido.maintainer_list,
+        },
+        *opts.amount(),
+        from_validator_index,
+        to_validator_index,
+        maintainer_index,
+    );
+    send_or_dump_transaction(
+        config,
+        "rebalance stake",
+        &[instruction],
+        &[config.signer],
+    )?;
+
+    Ok(RebalanceStakeOutput {
+        from_vote_account: *from_validator.pubkey(),
+        to_vote_account: *to_validator.pubkey(),
+        rebalanced_amount: *opts.amount(),
+        destination_unstake_account,
+        destination_stake_account,
+    })
+}
+
+/// A freshly computed, and successfully recorded, off-chain performance
+/// reading for one validator.
+#[derive(Serialize)]
+pub struct ValidatorPerformanceReading {
+    #[serde(serialize_with = "serialize_b58")]
+    pub vote_account_address: Pubkey,
+
+    /// Fraction of this validator's leader slots in which it produced a
+    /// block so far this epoch.
+    pub block_production_rate: f64,
+
+    /// Fraction of the vote credits attainable so far this epoch that this
+    /// validator actually earned.
+    pub vote_success_rate: f64,
+}
+
+#[derive(Serialize)]
+pub struct CollectValidatorPerformanceOutput {
+    /// Validators whose reading was computed and written on-chain.
+    pub updated: Vec<ValidatorPerformanceReading>,
+
+    /// Validators skipped because `getBlockProduction` had no leader slots
+    /// recorded for them yet this epoch.
+    pub skipped: Vec<String>,
+}
+
+impl fmt::Display for CollectValidatorPerformanceOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Updated {} validator(s):", self.updated.len())?;
+        for reading in &self.updated {
+            writeln!(
+                f,
+                "  - {}: block production {:.2}%, vote success {:.2}%",
+                reading.vote_account_address,
+                100.0 * reading.block_production_rate,
+                100.0 * reading.vote_success_rate,
+            )?;
+        }
+        if !self.skipped.is_empty() {
+            writeln!(f, "Skipped {} validator(s) with no data yet:", self.skipped.len())?;
+            for vote_account_address in &self.skipped {
+                writeln!(f, "  - {}", vote_account_address)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// CLI entry point for the maintainer-run performance oracle: computes each
+/// validator's block-production and vote-success rate for the epoch so far,
+/// and records the reading on chain.
+///
+/// Data center concentration and superminority status are not computed here;
+/// they come from a separate topology feed. This command preserves whatever
+/// values that feed last wrote rather than overwriting them, so it can run
+/// on its own epoch cadence without resetting `ValidatorPerf::meets_criteria`'s
+/// topology checks back to `0`/`false`.
+pub fn command_collect_validator_performance(
+    config: &mut SnapshotConfig,
+    opts: &CollectValidatorPerformanceOpts,
+) -> solido_cli_common::Result<CollectValidatorPerformanceOutput> {
+    let solido = config.client.get_solido(opts.solido_address())?;
+    let validators = config
+        .client
+        .get_account_list::<Validator>(&solido.validator_list)?
+        .entries;
+    let validator_perfs = config
+        .client
+        .get_account_list::<ValidatorPerf>(&solido.validator_perf_list)?;
+
+    let clock = config.client.get_clock()?;
+    let epoch_schedule = config.client.get_epoch_schedule()?;
+    let current_epoch = clock.epoch;
+    let slots_elapsed = clock
+        .slot
+        .saturating_sub(epoch_schedule.get_first_slot_in_epoch(current_epoch))
+        .max(1);
+
+    let block_production = config.client.get_block_production()?;
+
+    let mut updated = Vec::new();
+    let mut skipped = Vec::new();
+
+    for validator in &validators {
+        let vote_account = config.client.get_vote_account(validator.pubkey())?;
+
+        let reading = block_production.get(&vote_account.node_pubkey).copied();
+        let (leader_slots, blocks_produced) = match reading {
+            Some((leader_slots, blocks_produced)) if leader_slots > 0 => {
+                (leader_slots as u64, blocks_produced as u64)
+            }
+            _ => {
+                skipped.push(validator.pubkey().to_string());
+                continue;
+            }
+        };
+        let block_production_rate = per64(blocks_produced, leader_slots);
+
+        let (credits_start, credits_end) = vote_account
+            .epoch_credits
+            .iter()
+            .find(|(epoch, _, _)| *epoch == current_epoch)
+            .map(|(_, credits, prev_credits)| (*prev_credits, *credits))
+            .unwrap_or((0, 0));
+        let credits_earned = credits_end.saturating_sub(credits_start);
+        let vote_success_rate = per64(credits_earned.min(slots_elapsed), slots_elapsed);
+
+        // `data_center_stake_concentration` and `in_superminority` are topology
+        // readings written by a separate feed, not by this command. Preserve
+        // whatever is already on chain instead of clobbering it back to the
+        // zero value every epoch.
+        let (data_center_stake_concentration, in_superminority) =
+            maintainer::find(&validator_perfs.entries, |perf: &ValidatorPerf| {
+                perf.pubkey() == validator.pubkey()
+            })
+            .and_then(|perf| perf.rest.as_ref())
+            .map_or((0, false), |rest| {
+                (rest.data_center_stake_concentration, rest.in_superminority)
+            });
+
+        send_or_dump_transaction(
+            config,
+            "update offchain validator perf",
+            &[lido::instruction::update_offchain_validator_perf(
+                opts.solido_program_id(),
+                block_production_rate,
+                vote_success_rate,
+                data_center_stake_concentration,
+                in_superminority,
+                &lido::instruction::UpdateOffchainValidatorPerfAccountsMeta {
+                    lido: *opts.solido_address(),
+                    validator_vote_account_to_update: *validator.pubkey(),
+                    validator_list: solido.validator_list,
+                    validator_perf_list: solido.validator_perf_list,
+                },
+            )],
+            &[config.signer],
+        )?;
+
+        updated.push(ValidatorPerformanceReading {
+            vote_account_address: *validator.pubkey(),
+            block_production_rate: to_f64(block_production_rate),
+            vote_success_rate: to_f64(vote_success_rate),
+        });
+    }
+
+    Ok(CollectValidatorPerformanceOutput { updated, skipped })
+}
+
+#[derive(Serialize)]
+pub struct ShowSolidoAuthoritiesOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub solido_program_id: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    pub solido_address: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    pub reserve_account: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    pub stake_authority: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    pub mint_authority: Pubkey,
+}
+
+impl fmt::Display for ShowSolidoAuthoritiesOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Stake authority:            {}", self.stake_authority,)?;
+        writeln!(f, "Mint authority:             {}", self.mint_authority)?;
+        writeln!(f, "Reserve account:            {}", self.reserve_account)?;
+        Ok(())
+    }
+}
+
+pub fn command_show_solido_authorities(
+    opts: &ShowSolidoAuthoritiesOpts,
+) -> solido_cli_common::Result<ShowSolidoAuthoritiesOutput> {
+    let (reserve_account, _) = find_authority_program_address(
+        opts.solido_program_id(),
+        opts.solido_address(),
+        RESERVE_ACCOUNT,
+    );
+    let (mint_authority, _) = find_authority_program_address(
+        opts.solido_program_id(),
+        opts.solido_address(),
+        MINT_AUTHORITY,
+    );
+    let (stake_authority, _) = find_authority_program_address(
+        opts.solido_program_id(),
+        opts.solido_address(),
+        STAKE_AUTHORITY,
+    );
+    Ok(ShowSolidoAuthoritiesOutput {
+        solido_program_id: *opts.solido_program_id(),
+        solido_address: *opts.solido_address(),
+        reserve_account,
+        stake_authority,
+        mint_authority,
+    })
+}
+
+#[derive(Serialize)]
+pub struct DepositOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub recipient: Pubkey,
+
+    /// Amount of stSOL we expected to receive based on the exchange rate at the time of the deposit.
+    ///
+    /// This can differ from the actual amount, when a deposit happens close to
+    /// an epoch boundary, and an `UpdateExchangeRate` instruction executed before
+    /// our deposit, but after we checked the exchange rate.
+    #[serde(rename = "expected_st_lamports")]
+    pub expected_st_sol: StLamports,
+
+    /// The difference in stSOL balance before and after our deposit.
+    ///
+    /// If no other transactions touch the recipient account, then this is the
+    /// amount of stSOL we got. However, the stSOL account balance might change
+    /// for other reasons than just the deposit, if another transaction touched
+    /// the account in the same block.
+    #[serde(rename = "st_lamports_balance_increase")]
+    pub st_sol_balance_increase: StLamports,
+
+    /// Whether we had to create the associated stSOL account. False if one existed already.
+    pub created_associated_st_sol_account: bool,
+}
+
+impl fmt::Display for DepositOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.created_associated_st_sol_account {
+            writeln!(f, "Created recipient stSOL account, it did not yet exist.")?;
+        } else {
+            writeln!(f, "Recipient stSOL account existed already before deposit.")?;
+        }
+        writeln!(f, "Recipient stSOL account: {}", self.recipient)?;
+        writeln!(f, "Expected stSOL amount:   {}", self.expected_st_sol)?;
+        writeln!(
+            f,
+            "stSOL balance increase:  {}",
+            self.st_sol_balance_increase
+        )?;
+        Ok(())
+    }
+}
+
+/// Get (creating it if needed) the signer's associated stSOL account.
+///
+/// Shared by `command_deposit` and `command_deposit_stake`, which both need
+/// somewhere to mint the resulting stSOL to.
+fn ensure_st_sol_recipient(
+    config: &mut SnapshotConfig,
+    st_sol_mint: &Pubkey,
+) -> solido_cli_common::Result<(Pubkey, bool)> {
+    let recipient =
+        spl_associated_token_account::get_associated_token_address(&config.signer.pubkey(), st_sol_mint);
+
+    if !config.client.account_exists(&recipient)? {
+        let instr = spl_associated_token_account::create_associated_token_account(
+            &config.signer.pubkey(),
+            &config.signer.pubkey(),
+            st_sol_mint,
+        );
+
+        send_or_dump_transaction(
+            config,
+            "create associated stSOL account",
+            &[instr],
+            &[config.signer],
+        )?;
+
+        Ok((recipient, true))
+    } else {
+        Ok((recipient, false))
+    }
+}
+
+pub fn command_deposit(
+    config: &mut SnapshotClientConfig,
+    opts: &DepositOpts,
+) -> std::result::Result<DepositOutput, Error> {
+    let (recipient, created_recipient) = config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+        ensure_st_sol_recipient(config, &solido.st_sol_mint)
+    })?;
+
+    let (balance_before, exchange_rate) = config.with_snapshot(|config| {
+        let balance_before = config
+            .client
+            .get_spl_token_balance(&recipient)
+            .map(StLamports)?;
+        let solido = config.client.get_solido(opts.solido_address())?;
+        let reserve =
+            solido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
+        let mint_authority =
+            solido.get_mint_authority(opts.solido_program_id(), opts.solido_address())?;
+
+        let instr = lido::instruction::deposit(
+            opts.solido_program_id(),
+            &lido::instruction::DepositAccountsMeta {
+                lido: *opts.solido_address(),
+                user: config.signer.pubkey(),
+                recipient,
+                st_sol_mint: solido.st_sol_mint,
+                mint_authority,
+                reserve_account: reserve,
+            },
+            *opts.amount_sol(),
+        );
+
+        send_or_dump_transaction(config, "deposit", &[instr], &[config.signer])?;
+
+        Ok((balance_before, solido.exchange_rate))
+    })?;
+
+    let balance_after = config.with_snapshot(|config| {
+        config
+            .client
+            .get_spl_token_balance(&recipient)
+            .map(StLamports)
+    })?;
+
+    let st_sol_balance_increase = StLamports(balance_after.0.saturating_sub(balance_before.0));
+    let expected_st_sol = exchange_rate
+        .exchange_sol(*opts.amount_sol())
+        // If this is not an `Ok`, the transaction should have failed, but if
+        // the transaction did not fail, then we do want to show the output; we
+        // don't want the user to think that the deposit failed.
+        .unwrap_or(StLamports(0));
+
+    let result = DepositOutput {
+        recipient,
+        expected_st_sol,
+        st_sol_balance_increase,
+        created_associated_st_sol_account: created_recipient,
+    };
+    Ok(result)
+}
+
+#[derive(Serialize)]
+pub struct DepositWithLockupOutput {
+    /// The freshly created vesting account that tracks the unlock schedule.
+    #[serde(serialize_with = "serialize_b58")]
+    pub vesting_address: Pubkey,
+
+    /// The account that will be entitled to claim the unlocked stSOL over time.
+    #[serde(serialize_with = "serialize_b58")]
+    pub beneficiary: Pubkey,
+
+    /// Amount of stSOL placed in the vesting vault, based on `solido.exchange_rate`
+    /// at the time of the deposit.
+    #[serde(rename = "vested_st_lamports")]
+    pub vested_st_sol: StLamports,
+}
+
+impl fmt::Display for DepositWithLockupOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Vesting account:    {}", self.vesting_address)?;
+        writeln!(f, "Beneficiary:        {}", self.beneficiary)?;
+        writeln!(f, "Vested stSOL amount: {}", self.vested_st_sol)?;
+        Ok(())
+    }
+}
+
+/// Deposit SOL on behalf of `beneficiary`, locking the resulting stSOL in a
+/// program-controlled vault instead of minting it directly into a freely
+/// owned token account.
+///
+/// The vault unlocks linearly between `start_ts` and `end_ts`; the
+/// beneficiary claims the unlocked portion over time with
+/// `command_claim_vested`.
+pub fn command_deposit_with_lockup(
+    config: &mut SnapshotClientConfig,
+    opts: &DepositWithLockupOpts,
+) -> std::result::Result<DepositWithLockupOutput, Error> {
+    let vesting_signer = from_key_path_or_random(opts.vesting_key_path())?;
+
+    config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+        let reserve =
+            solido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
+        let mint_authority =
+            solido.get_mint_authority(opts.solido_program_id(), opts.solido_address())?;
+        let (vault_authority, _) = lido::find_authority_program_address(
+            opts.solido_program_id(),
+            opts.solido_address(),
+            lido::VESTING_VAULT_AUTHORITY,
+        );
+        let st_sol_vault = spl_associated_token_account::get_associated_token_address(
+            &vault_authority,
+            &solido.st_sol_mint,
+        );
+
+        let instr = lido::instruction::deposit_with_vesting(
+            opts.solido_program_id(),
+            &lido::instruction::DepositWithVestingAccountsMeta {
+                lido: *opts.solido_address(),
+                user: config.signer.pubkey(),
+                vesting: vesting_signer.pubkey(),
+                vault_authority,
+                st_sol_vault,
+                st_sol_mint: solido.st_sol_mint,
+                mint_authority,
+                reserve_account: reserve,
+            },
+            *opts.amount_sol(),
+            *opts.beneficiary(),
+            *opts.start_ts(),
+            *opts.end_ts(),
+            *opts.period_count(),
+        );
+
+        send_or_dump_transaction(
+            config,
+            "deposit with lockup",
+            &[instr],
+            &[config.signer, &*vesting_signer],
+        )?;
+
+        Ok(DepositWithLockupOutput {
+            vesting_address: vesting_signer.pubkey(),
+            beneficiary: *opts.beneficiary(),
+            vested_st_sol: solido
+                .exchange_rate
+                .exchange_sol(*opts.amount_sol())
+                .unwrap_or(StLamports(0)),
+        })
+    })
+}
+
+#[derive(Serialize)]
+pub struct ClaimVestedOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub vesting_address: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    pub recipient: Pubkey,
+
+    /// The difference in the recipient's stSOL balance before and after the claim.
+    #[serde(rename = "claimed_st_lamports")]
+    pub claimed_st_sol: StLamports,
+
+    /// Whether we had to create the recipient's associated stSOL account.
+    pub created_associated_st_sol_account: bool,
+}
+
+impl fmt::Display for ClaimVestedOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.created_associated_st_sol_account {
+            writeln!(f, "Created recipient stSOL account, it did not yet exist.")?;
+        }
+        writeln!(f, "Vesting account:  {}", self.vesting_address)?;
+        writeln!(f, "Recipient:        {}", self.recipient)?;
+        writeln!(f, "Claimed stSOL amount: {}", self.claimed_st_sol)?;
+        Ok(())
+    }
+}
+
+/// Claim the currently-unlocked stSOL from a vesting vault created by
+/// `command_deposit_with_lockup`, transferring it to the beneficiary's own
+/// stSOL account.
+pub fn command_claim_vested(
+    config: &mut SnapshotClientConfig,
+    opts: &ClaimVestedOpts,
+) -> std::result::Result<ClaimVestedOutput, Error> {
+    let (recipient, created_recipient) = config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+        ensure_st_sol_recipient(config, &solido.st_sol_mint)
+    })?;
+
+    let claimed_st_sol = config.with_snapshot(|config| {
+        let balance_before = config
+            .client
+            .get_spl_token_balance(&recipient)
+            .map(StLamports)?;
+
+        let solido = config.client.get_solido(opts.solido_address())?;
+        let (vault_authority, _) = lido::find_authority_program_address(
+            opts.solido_program_id(),
+            opts.solido_address(),
+            lido::VESTING_VAULT_AUTHORITY,
+        );
+        let st_sol_vault = spl_associated_token_account::get_associated_token_address(
+            &vault_authority,
+            &solido.st_sol_mint,
+        );
+
+        let instr = lido::instruction::claim_vested(
+            opts.solido_program_id(),
+            &lido::instruction::ClaimVestedAccountsMeta {
+                lido: *opts.solido_address(),
+                vesting: *opts.vesting_address(),
+                vault_authority,
+                st_sol_vault,
+                beneficiary: config.signer.pubkey(),
+                recipient,
+            },
+        );
+        send_or_dump_transaction(config, "claim vested", &[instr], &[config.signer])?;
+
+        let balance_after = config
+            .client
+            .get_spl_token_balance(&recipient)
+            .map(StLamports)?;
+        Ok(StLamports(
+            balance_after.0.saturating_sub(balance_before.0),
+        ))
+    })?;
+
+    Ok(ClaimVestedOutput {
+        vesting_address: *opts.vesting_address(),
+        recipient,
+        claimed_st_sol,
+        created_associated_st_sol_account: created_recipient,
+    })
+}
+
+#[derive(Serialize)]
+pub struct DepositStakeOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub recipient: Pubkey,
+
+    /// The validator the deposited stake account was delegated to.
+    #[serde(serialize_with = "serialize_b58")]
+    pub validator_vote_account: Pubkey,
+
+    /// Amount of stSOL minted, based on `solido.exchange_rate` at the time of the deposit.
+    #[serde(rename = "minted_st_lamports")]
+    pub minted_st_sol: StLamports,
+
+    /// Whether we had to create the associated stSOL account. False if one existed already.
+    pub created_associated_st_sol_account: bool,
+}
+
+impl fmt::Display for DepositStakeOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.created_associated_st_sol_account {
+            writeln!(f, "Created recipient stSOL account, it did not yet exist.")?;
+        } else {
+            writeln!(f, "Recipient stSOL account existed already before deposit.")?;
+        }
+        writeln!(f, "Recipient stSOL account: {}", self.recipient)?;
+        writeln!(
+            f,
+            "Deposited into validator: {}",
+            self.validator_vote_account
+        )?;
+        writeln!(f, "Minted stSOL amount:     {}", self.minted_st_sol)?;
+        Ok(())
+    }
+}
+
+/// Deposit an existing, fully-activated stake account in exchange for stSOL.
+///
+/// Unlike `command_deposit`, this does not go through the reserve and does
+/// not wait out the usual warm-up epoch: the stake account keeps its
+/// existing activation, it is merely re-delegated to Solido's authority and
+/// folded into the target validator's stake accounts.
+pub fn command_deposit_stake(
+    config: &mut SnapshotClientConfig,
+    opts: &DepositStakeOpts,
+) -> std::result::Result<DepositStakeOutput, Error> {
+    config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+        let validators = config
+            .client
+            .get_account_list::<Validator>(&solido.validator_list)?;
+
+        let stake_authority =
+            solido.get_stake_authority(opts.solido_program_id(), opts.solido_address())?;
+
+        let stake_account = config.client.get_account(opts.stake_account())?;
+        let stake = lido::stake_account::deserialize_stake_account(&stake_account.data)
+            .map_err(|err| CliError::with_cause("Failed to read the deposited stake account.", err))?;
+        let validator_vote_account = stake.delegation.voter_pubkey;
+
+        let validator = validators.find(&validator_vote_account).ok_or_else(|| {
+            CliError::new("The deposited stake account is not delegated to a Solido validator.")
+        })?;
+        let validator_index = validators
+            .position(validator.pubkey())
+            .ok_or_else(|| CliError::new("Pubkey not found in validator list"))?;
+
+        let (recipient, created_recipient) =
+            ensure_st_sol_recipient(config, &solido.st_sol_mint)?;
+
+        let stake_lamports = Lamports(stake_account.lamports());
+        let instr = lido::instruction::deposit_stake_account(
+            opts.solido_program_id(),
+            &lido::instruction::DepositStakeAccountsMeta {
+                lido: *opts.solido_address(),
+                validator_list: solido.validator_list,
+                stake_account: *opts.stake_account(),
+                stake_authority,
+                validator_vote_account,
+                staker: config.signer.pubkey(),
+                st_sol_mint: solido.st_sol_mint,
+                recipient,
+            },
+            validator_index,
+        );
+        send_or_dump_transaction(config, "deposit stake", &[instr], &[config.signer])?;
+
+        let minted_st_sol = solido
+            .exchange_rate
+            .exchange_sol(stake_lamports)
+            // If this is not an `Ok`, the transaction should have failed, but if
+            // the transaction did not fail, we still want to show the output.
+            .unwrap_or(StLamports(0));
+
+        Ok(DepositStakeOutput {
+            recipient,
+            validator_vote_account,
+            minted_st_sol,
+            created_associated_st_sol_account: created_recipient,
+        })
+    })
+}
+
+/// Whether a freshly split-off stake account is already earning rewards.
+///
+/// A stake account inherits its source account's delegation when it is
+/// split, so it does not start out undelegated; it only needs to wait out
+/// the usual activation warm-up before it is fully `Active`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum StakeAccountActivation {
+    Activating,
+    Active,
+}
+
+impl fmt::Display for StakeAccountActivation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StakeAccountActivation::Activating => write!(f, "activating"),
+            StakeAccountActivation::Active => write!(f, "active"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct WithdrawOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub from_token_address: Pubkey,
+
+    /// Amount of SOL that was withdrawn.
+    pub withdrawn_sol: Lamports,
+
+    /// Newly created stake account, where the source stake account will be
+    /// split to.
+    #[serde(serialize_with = "serialize_b58")]
+    pub new_stake_account: Pubkey,
+
+    /// Whether `new_stake_account` has finished activating yet. The account
+    /// is delegated to the same validator as the source stake account it was
+    /// split from, so the user can hold it as a native stake account
+    /// instead of waiting on reserve liquidity or paying liquidity-pool
+    /// spread to exit through stSOL.
+    pub new_stake_account_activation: StakeAccountActivation,
+}
+
+impl fmt::Display for WithdrawOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Withdrawn from:          {}", self.from_token_address)?;
+        writeln!(f, "Total SOL withdrawn:     {}", self.withdrawn_sol)?;
+        writeln!(f, "New stake account:       {}", self.new_stake_account)?;
+        writeln!(
+            f,
+            "New stake account state: {}",
+            self.new_stake_account_activation
+        )?;
+        Ok(())
+    }
+}
+
+pub fn command_withdraw(
+    config: &mut SnapshotClientConfig,
+    opts: &WithdrawOpts,
+) -> std::result::Result<WithdrawOutput, Error> {
+    let (st_sol_address, new_stake_account) = config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+
+        let validators = config
+            .client
+            .get_account_list::<Validator>(&solido.validator_list)?;
 
         let st_sol_address = spl_associated_token_account::get_associated_token_address(
             &config.signer.pubkey(),
@@ -1145,51 +2568,220 @@ pub fn command_withdraw(
             *opts.amount_st_sol(),
             validator_index,
         );
-        config.sign_and_send_transaction(&[instr], &[config.signer, &destination_stake_account])?;
+        send_or_dump_transaction(
+            config,
+            "withdraw",
+            &[instr],
+            &[config.signer, &destination_stake_account],
+        )?;
 
         Ok((st_sol_address, destination_stake_account))
     })?;
 
-    let stake_sol = config.with_snapshot(|config| {
+    let (stake_sol, new_stake_account_activation) = config.with_snapshot(|config| {
         let stake_account = config.client.get_account(&new_stake_account.pubkey())?;
-        Ok(Lamports(stake_account.lamports()))
+        let stake = lido::stake_account::deserialize_stake_account(&stake_account.data)
+            .map_err(|err| CliError::with_cause("Failed to read new stake account.", err))?;
+        let current_epoch = config.client.get_clock()?.epoch;
+        let activation = if stake.delegation.activation_epoch >= current_epoch {
+            StakeAccountActivation::Activating
+        } else {
+            StakeAccountActivation::Active
+        };
+        Ok((Lamports(stake_account.lamports()), activation))
     })?;
     let result = WithdrawOutput {
         from_token_address: st_sol_address,
         withdrawn_sol: stake_sol,
         new_stake_account: new_stake_account.pubkey(),
+        new_stake_account_activation,
     };
     Ok(result)
 }
 
+/// Pick the active validator with the least stake, to redelegate towards.
+///
+/// This is the mirror image of `get_validator_to_withdraw`, which picks the
+/// validator with the most stake to withdraw from.
+fn get_validator_to_redelegate_to(validators: &AccountList<Validator>) -> Result<&Validator, CliError> {
+    validators
+        .entries
+        .iter()
+        .filter(|validator| validator.is_active())
+        .min_by_key(|validator| validator.effective_stake_balance)
+        .ok_or_else(|| CliError::new("There are no active validators to redelegate to."))
+}
+
+#[derive(Serialize)]
+pub struct RedelegateOutput {
+    /// Validator that the stake was redelegated away from.
+    #[serde(serialize_with = "serialize_b58")]
+    pub source_vote_account: Pubkey,
+
+    /// Validator that the stake was redelegated to.
+    #[serde(serialize_with = "serialize_b58")]
+    pub destination_vote_account: Pubkey,
+
+    /// Amount of stake that was moved.
+    pub redelegated_lamports: Lamports,
+
+    /// Newly created stake account that the redelegated stake now lives in.
+    #[serde(serialize_with = "serialize_b58")]
+    pub new_stake_account: Pubkey,
+}
+
+impl fmt::Display for RedelegateOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Source validator:      {}", self.source_vote_account)?;
+        writeln!(
+            f,
+            "Destination validator: {}",
+            self.destination_vote_account
+        )?;
+        writeln!(f, "Redelegated lamports:  {}", self.redelegated_lamports)?;
+        writeln!(f, "New stake account:     {}", self.new_stake_account)?;
+        Ok(())
+    }
+}
+
+/// Move an active stake account's delegation to a different validator in a
+/// single instruction, using `StakeInstruction::Redelegate`.
+///
+/// Unlike `command_withdraw` followed by a fresh deposit, this does not park
+/// the stake in the reserve for an epoch: the destination stake account
+/// inherits the source's activation, so it keeps earning rewards throughout.
+pub fn command_redelegate(
+    config: &mut SnapshotClientConfig,
+    opts: &RedelegateOpts,
+) -> std::result::Result<RedelegateOutput, Error> {
+    config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+
+        let validators = config
+            .client
+            .get_account_list::<Validator>(&solido.validator_list)?;
+
+        let stake_authority =
+            solido.get_stake_authority(opts.solido_program_id(), opts.solido_address())?;
+
+        let source_validator = validators
+            .find(opts.source_vote_account())
+            .ok_or_else(|| CliError::new("Source validator is not part of this Solido instance."))?;
+
+        let destination_validator = match opts.destination_vote_account() {
+            Some(destination_vote_account) => validators.find(destination_vote_account).ok_or_else(
+                || CliError::new("Destination validator is not part of this Solido instance."),
+            )?,
+            None => get_validator_to_redelegate_to(&validators)?,
+        };
+
+        let (source_stake_account, _bump_seed) = source_validator.find_stake_account_address(
+            opts.solido_program_id(),
+            opts.solido_address(),
+            source_validator.stake_seeds.begin,
+            StakeType::Stake,
+        );
+        let redelegated_lamports =
+            Lamports(config.client.get_account(&source_stake_account)?.lamports());
+
+        let destination_stake_account = Keypair::new();
+        let source_validator_index = validators
+            .position(source_validator.pubkey())
+            .ok_or_else(|| CliError::new("Pubkey not found in validator list"))?;
+        let destination_validator_index = validators
+            .position(destination_validator.pubkey())
+            .ok_or_else(|| CliError::new("Pubkey not found in validator list"))?;
+
+        let instr = lido::instruction::redelegate(
+            opts.solido_program_id(),
+            &lido::instruction::RedelegateAccountsMeta {
+                lido: *opts.solido_address(),
+                validator_list: solido.validator_list,
+                source_validator_vote_account: *source_validator.pubkey(),
+                source_stake_account,
+                destination_validator_vote_account: *destination_validator.pubkey(),
+                destination_stake_account: destination_stake_account.pubkey(),
+                stake_authority,
+            },
+            source_validator_index,
+            destination_validator_index,
+        );
+        send_or_dump_transaction(
+            config,
+            "redelegate",
+            &[instr],
+            &[config.signer, &destination_stake_account],
+        )?;
+
+        Ok(RedelegateOutput {
+            source_vote_account: *source_validator.pubkey(),
+            destination_vote_account: *destination_validator.pubkey(),
+            redelegated_lamports,
+            new_stake_account: destination_stake_account.pubkey(),
+        })
+    })
+}
+
 #[derive(Serialize)]
 pub struct DeactivateIfViolatesOutput {
-    // List of validators that exceeded max commission
+    // List of validators that violated one or more criteria.
     entries: Vec<ValidatorViolationInfo>,
     max_commission_percentage: u8,
+    min_block_production_rate: u64,
+    min_vote_success_rate: u64,
 }
 
 #[derive(Serialize)]
 struct ValidatorViolationInfo {
     #[serde(serialize_with = "serialize_b58")]
     pub validator_vote_account: Pubkey,
-    pub commission: u8,
+
+    /// The on-chain commission, if that is why this validator was flagged.
+    pub commission: Option<u8>,
+
+    /// The off-chain block-production rate, if that is why this validator
+    /// was flagged.
+    pub block_production_rate: Option<u64>,
+
+    /// The off-chain vote-success rate, if that is why this validator was
+    /// flagged.
+    pub vote_success_rate: Option<u64>,
 }
 
 impl fmt::Display for DeactivateIfViolatesOutput {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(
             f,
-            "Maximum validation commission: {}",
+            "Maximum validation commission: {}%",
             self.max_commission_percentage
         )?;
+        writeln!(
+            f,
+            "Minimum block production rate: {:.2}%",
+            100.0 * to_f64(self.min_block_production_rate)
+        )?;
+        writeln!(
+            f,
+            "Minimum vote success rate:     {:.2}%",
+            100.0 * to_f64(self.min_vote_success_rate)
+        )?;
 
         for entry in &self.entries {
-            writeln!(
-                f,
-                "Validator vote account: {}, validation commission: {}",
-                entry.validator_vote_account, entry.commission
-            )?;
+            write!(f, "Validator vote account: {}", entry.validator_vote_account)?;
+            if let Some(commission) = entry.commission {
+                write!(f, ", commission violation: {}%", commission)?;
+            }
+            if let Some(rate) = entry.block_production_rate {
+                write!(
+                    f,
+                    ", block production violation: {:.2}%",
+                    100.0 * to_f64(rate)
+                )?;
+            }
+            if let Some(rate) = entry.vote_success_rate {
+                write!(f, ", vote success violation: {:.2}%", 100.0 * to_f64(rate))?;
+            }
+            writeln!(f)?;
         }
         Ok(())
     }
@@ -1205,6 +2797,10 @@ pub fn command_deactivate_if_violates(
     let validators = config
         .client
         .get_account_list::<Validator>(&solido.validator_list)?;
+    let validator_perfs = config
+        .client
+        .get_account_list::<ValidatorPerf>(&solido.validator_perf_list)?;
+    let current_epoch = config.client.get_clock()?.epoch;
 
     let mut violations = vec![];
     let mut instructions = vec![];
@@ -1215,7 +2811,31 @@ pub fn command_deactivate_if_violates(
             .ok()
             .ok_or_else(|| CliError::new("Validator account data too small"))?;
 
-        if !validator.is_active() || commission <= solido.criteria.max_commission {
+        let commission_violation =
+            (commission > solido.criteria.max_commission).then_some(commission);
+
+        // Off-chain readings older than the previous epoch are considered
+        // stale, so they cannot by themselves trigger a deactivation.
+        let recent_rest = maintainer::find(&validator_perfs.entries, |perf: &ValidatorPerf| {
+            perf.pubkey() == vote_pubkey
+        })
+        .and_then(|perf| perf.rest.as_ref())
+        .filter(|rest| current_epoch.saturating_sub(rest.updated_at) <= 1);
+
+        let block_production_violation = recent_rest.and_then(|rest| {
+            (rest.block_production_rate < solido.criteria.min_block_production_rate)
+                .then_some(rest.block_production_rate)
+        });
+        let vote_success_violation = recent_rest.and_then(|rest| {
+            (rest.vote_success_rate < solido.criteria.min_vote_success_rate)
+                .then_some(rest.vote_success_rate)
+        });
+
+        if !validator.is_active()
+            || (commission_violation.is_none()
+                && block_production_violation.is_none()
+                && vote_success_violation.is_none())
+        {
             continue;
         }
 
@@ -1231,7 +2851,9 @@ pub fn command_deactivate_if_violates(
         instructions.push(instruction);
         violations.push(ValidatorViolationInfo {
             validator_vote_account: *validator.pubkey(),
-            commission,
+            commission: commission_violation,
+            block_production_rate: block_production_violation,
+            vote_success_rate: vote_success_violation,
         });
     }
 
@@ -1239,11 +2861,13 @@ pub fn command_deactivate_if_violates(
     // Due to the fact that Solana has a limit on number of instructions in a transaction
     // this can fall if there would be a lot of misbehaved validators each
     // exceeding `max_commission_percentage`. But it is a very improbable scenario.
-    config.sign_and_send_transaction(&instructions, &signers)?;
+    send_or_dump_transaction(config, "deactivate if violates", &instructions, &signers)?;
 
     Ok(DeactivateIfViolatesOutput {
         entries: violations,
         max_commission_percentage: solido.criteria.max_commission,
+        min_block_production_rate: solido.criteria.min_block_production_rate,
+        min_vote_success_rate: solido.criteria.min_vote_success_rate,
     })
 }
 
@@ -1251,7 +2875,7 @@ pub fn command_deactivate_if_violates(
 pub fn command_remove_validator(
     config: &mut SnapshotConfig,
     opts: &RemoveValidatorOpts,
-) -> solido_cli_common::Result<ProposeInstructionOutput> {
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
     let solido = config.client.get_solido(opts.solido_address())?;
 
     let validators = config
@@ -1273,8 +2897,9 @@ pub fn command_remove_validator(
             .position(opts.validator_vote_account())
             .ok_or_else(|| CliError::new("Pubkey not found in validator list"))?,
     );
-    propose_instruction(
+    propose_or_dump_instruction(
         config,
+        "remove validator",
         opts.multisig_program_id(),
         *opts.multisig_address(),
         instruction,
@@ -1285,7 +2910,7 @@ pub fn command_remove_validator(
 pub fn command_change_criteria(
     config: &mut SnapshotConfig,
     opts: &ChangeCriteriaOpts,
-) -> solido_cli_common::Result<ProposeInstructionOutput> {
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
     let (multisig_address, _) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
 
@@ -1299,10 +2924,111 @@ pub fn command_change_criteria(
             max_commission: *opts.max_commission(),
             min_block_production_rate: *opts.min_block_production_rate(),
             min_vote_success_rate: *opts.min_vote_success_rate(),
+            ..Criteria::default()
+        },
+    );
+    propose_or_dump_instruction(
+        config,
+        "change criteria",
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// The subset of a Solido v2 deployment's addresses that the published
+/// deployments page expects, so a freshly created instance can be dropped
+/// straight into `solido --config` without hand-editing.
+#[derive(Serialize)]
+struct SolidoV2Config {
+    cluster: String,
+    #[serde(serialize_with = "serialize_b58")]
+    multisig_program_id: Pubkey,
+    #[serde(serialize_with = "serialize_b58")]
+    multisig_address: Pubkey,
+    #[serde(serialize_with = "serialize_b58")]
+    solido_program_id: Pubkey,
+    #[serde(serialize_with = "serialize_b58")]
+    solido_address: Pubkey,
+    #[serde(serialize_with = "serialize_b58")]
+    st_sol_mint: Pubkey,
+    #[serde(serialize_with = "serialize_b58")]
+    validator_list_address: Pubkey,
+    #[serde(serialize_with = "serialize_b58")]
+    maintainer_list_address: Pubkey,
+    #[serde(serialize_with = "serialize_b58")]
+    developer_fee_address: Pubkey,
+}
+
+/// Write `config` as pretty JSON to `path`, if one was given with `--emit-config`.
+///
+/// This is a best-effort convenience: failures here should not fail the
+/// surrounding command, since the on-chain transaction it is reporting on
+/// has already gone through.
+fn emit_config_file(path: &Option<PathBuf>, config: &SolidoV2Config) -> solido_cli_common::Result<()> {
+    if let Some(path) = path {
+        let contents = serde_json::to_string_pretty(config)
+            .expect("SolidoV2Config always serializes to JSON.");
+        match std::fs::write(path, contents) {
+            Ok(()) => eprintln!("Wrote config file to {}", path.display()),
+            Err(err) => eprintln!(
+                "Failed to write config file to {}: {}",
+                path.display(),
+                err
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// CLI entry point to propose pausing deposits and withdrawals, as an
+/// emergency stop that does not require a state migration or program
+/// upgrade.
+///
+/// Like `command_change_criteria`, this only builds the instruction and
+/// hands it to the multisig, which signs it as the manager when the
+/// proposal is executed.
+pub fn command_pause(
+    config: &mut SnapshotConfig,
+    opts: &PauseOpts,
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::pause(
+        opts.solido_program_id(),
+        &lido::instruction::PauseMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+    );
+    propose_or_dump_instruction(
+        config,
+        "pause",
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to propose resuming deposits and withdrawals after a pause.
+pub fn command_resume(
+    config: &mut SnapshotConfig,
+    opts: &ResumeOpts,
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::resume(
+        opts.solido_program_id(),
+        &lido::instruction::ResumeMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
         },
     );
-    propose_instruction(
+    propose_or_dump_instruction(
         config,
+        "resume",
         opts.multisig_program_id(),
         *opts.multisig_address(),
         instruction,
@@ -1344,6 +3070,13 @@ impl fmt::Display for CreateV2AccountsOutput {
     }
 }
 
+/// Validator and maintainer list capacities Solido v2's list accounts are
+/// sized for. `command_verify_migration` checks an existing deployment's
+/// list accounts against these same capacities, so they live here once
+/// rather than as separately-guessed constants in each command.
+pub const V2_MAX_VALIDATORS: usize = 50_000;
+pub const V2_MAX_MAINTAINERS: usize = 5_000;
+
 /// CLI entry point to create new accounts for Solido v2.
 pub fn command_create_v2_accounts(
     config: &mut SnapshotConfig,
@@ -1352,12 +3085,12 @@ pub fn command_create_v2_accounts(
     let validator_list_signer = Keypair::new();
     let maintainer_list_signer = Keypair::new();
 
-    let validator_list_size = AccountList::<Validator>::required_bytes(50_000);
+    let validator_list_size = AccountList::<Validator>::required_bytes(V2_MAX_VALIDATORS);
     let validator_list_account_balance = config
         .client
         .get_minimum_balance_for_rent_exemption(validator_list_size)?;
 
-    let maintainer_list_size = AccountList::<Maintainer>::required_bytes(5_000);
+    let maintainer_list_size = AccountList::<Maintainer>::required_bytes(V2_MAX_MAINTAINERS);
     let maintainer_list_account_balance = config
         .client
         .get_minimum_balance_for_rent_exemption(maintainer_list_size)?;
@@ -1389,7 +3122,9 @@ pub fn command_create_v2_accounts(
         opts.solido_program_id(),
     ));
 
-    config.sign_and_send_transaction(
+    send_or_dump_transaction(
+        config,
+        "create v2 accounts",
         &instructions[..],
         &[
             config.signer,
@@ -1398,6 +3133,21 @@ pub fn command_create_v2_accounts(
             &developer_keypair,
         ],
     )?;
+    emit_config_file(
+        opts.emit_config_path(),
+        &SolidoV2Config {
+            cluster: opts.cluster().clone(),
+            multisig_program_id: *opts.multisig_program_id(),
+            multisig_address: *opts.multisig_address(),
+            solido_program_id: *opts.solido_program_id(),
+            solido_address: *opts.solido_address(),
+            st_sol_mint: *opts.st_sol_mint(),
+            validator_list_address: validator_list_signer.pubkey(),
+            maintainer_list_address: maintainer_list_signer.pubkey(),
+            developer_fee_address: developer_keypair.pubkey(),
+        },
+    )?;
+
     Ok(CreateV2AccountsOutput {
         validator_list_address: validator_list_signer.pubkey(),
         maintainer_list_address: maintainer_list_signer.pubkey(),
@@ -1409,11 +3159,26 @@ pub fn command_create_v2_accounts(
 pub fn command_migrate_state_to_v2(
     config: &mut SnapshotClientConfig,
     opts: &MigrateStateToV2Opts,
-) -> solido_cli_common::Result<ProposeInstructionOutput> {
-    let propose_output = config.with_snapshot(|config| {
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
+    let (propose_output, st_sol_mint) = config.with_snapshot(|config| {
         let (multisig_address, _) =
             get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
 
+        let st_sol_mint = config.client.get_solido(opts.solido_address())?.st_sol_mint;
+
+        if *opts.min_block_production_rate() > 10_000 {
+            return Err(CliError::new(
+                "--min-block-production-rate must be a basis-point value in the range 0-10000.",
+            )
+            .into());
+        }
+        if *opts.min_vote_success_rate() > 10_000 {
+            return Err(CliError::new(
+                "--min-vote-success-rate must be a basis-point value in the range 0-10000.",
+            )
+            .into());
+        }
+
         let instruction = lido::instruction::migrate_state_to_v2(
             opts.solido_program_id(),
             RewardDistribution {
@@ -1421,8 +3186,8 @@ pub fn command_migrate_state_to_v2(
                 developer_fee: *opts.developer_fee_share(),
                 st_sol_appreciation: *opts.st_sol_appreciation_share(),
             },
-            6_700,
-            5_000,
+            *opts.min_block_production_rate(),
+            *opts.min_vote_success_rate(),
             *opts.max_commission_percentage(),
             &lido::instruction::MigrateStateToV2Meta {
                 lido: *opts.solido_address(),
@@ -1434,13 +3199,238 @@ pub fn command_migrate_state_to_v2(
             },
         );
 
-        propose_instruction(
+        let propose_output = propose_or_dump_instruction(
             config,
+            "migrate state to v2",
             opts.multisig_program_id(),
             *opts.multisig_address(),
             instruction,
-        )
+        )?;
+
+        Ok((propose_output, st_sol_mint))
     })?;
 
+    emit_config_file(
+        opts.emit_config_path(),
+        &SolidoV2Config {
+            cluster: opts.cluster().clone(),
+            multisig_program_id: *opts.multisig_program_id(),
+            multisig_address: *opts.multisig_address(),
+            solido_program_id: *opts.solido_program_id(),
+            solido_address: *opts.solido_address(),
+            st_sol_mint,
+            validator_list_address: *opts.validator_list_address(),
+            maintainer_list_address: *opts.maintainer_list_address(),
+            developer_fee_address: *opts.developer_fee_address(),
+        },
+    )?;
+
     Ok(propose_output)
 }
+
+/// CLI entry point to propose setting (or revising) the stSOL mint's
+/// Metaplex token metadata, for Solido instances where the mint authority
+/// has already moved to the Solido PDA.
+///
+/// This mirrors `command_migrate_state_to_v2`'s flow: the actual CPI into
+/// `mpl_token_metadata` has to happen inside the program, since only the
+/// program can sign for the mint-authority PDA through `invoke_signed`, so
+/// here we only decide, based on whether the metadata account already
+/// exists, whether to propose a create or an update, and let the multisig
+/// sign off on it like any other governance action.
+pub fn command_set_solido_metadata(
+    config: &mut SnapshotClientConfig,
+    opts: &SetSolidoMetadataOpts,
+) -> solido_cli_common::Result<Option<ProposeInstructionOutput>> {
+    config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+        let mint_authority =
+            solido.get_mint_authority(opts.solido_program_id(), opts.solido_address())?;
+
+        let (metadata_address, _) = mpl_token_metadata::pda::find_metadata_account(&solido.st_sol_mint);
+        let metadata_exists = config.client.account_exists(&metadata_address)?;
+
+        let (multisig_address, _) =
+            get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+        let data = mpl_token_metadata::state::DataV2 {
+            name: opts.token_name().clone(),
+            symbol: opts.token_symbol().clone(),
+            uri: opts.token_uri().clone(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        };
+
+        let meta = lido::instruction::SetSolidoMetadataMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+            mint: solido.st_sol_mint,
+            mint_authority,
+            metadata_account: metadata_address,
+            payer: config.signer.pubkey(),
+        };
+
+        let instruction = if metadata_exists {
+            lido::instruction::update_solido_metadata(
+                opts.solido_program_id(),
+                &meta,
+                data,
+                *opts.is_mutable(),
+            )
+        } else {
+            lido::instruction::create_solido_metadata(
+                opts.solido_program_id(),
+                &meta,
+                data,
+                *opts.is_mutable(),
+            )
+        };
+
+        propose_or_dump_instruction(
+            config,
+            "set solido metadata",
+            opts.multisig_program_id(),
+            *opts.multisig_address(),
+            instruction,
+        )
+    })
+}
+
+/// Account-level sanity checks performed by `command_verify_migration` for
+/// one of the three list accounts passed to `migrate_state_to_v2`. The
+/// validator and maintainer lists are the ones `command_create_v2_accounts`
+/// creates; the validator perf list is checked against the validator list's
+/// capacity.
+#[derive(Serialize)]
+pub struct ListAccountCheck {
+    #[serde(serialize_with = "serialize_b58")]
+    pub address: Pubkey,
+    pub owner_is_solido_program: bool,
+    pub size_is_sufficient: bool,
+}
+
+#[derive(Serialize)]
+pub struct VerifyMigrationOutput {
+    pub old_reward_distribution: RewardDistribution,
+    pub new_reward_distribution: RewardDistribution,
+    pub validator_list: ListAccountCheck,
+    pub validator_perf_list: ListAccountCheck,
+    pub maintainer_list: ListAccountCheck,
+    pub max_commission_percentage_in_range: bool,
+
+    /// Log output from simulating the `migrate_state_to_v2` instruction
+    /// against the current chain state, without sending it.
+    pub simulation_logs: Vec<String>,
+}
+
+impl fmt::Display for VerifyMigrationOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Current reward distribution: {:?}", self.old_reward_distribution)?;
+        writeln!(f, "Proposed reward distribution: {:?}", self.new_reward_distribution)?;
+        writeln!(f)?;
+        for (name, check) in [
+            ("Validator list", &self.validator_list),
+            ("Validator perf list", &self.validator_perf_list),
+            ("Maintainer list", &self.maintainer_list),
+        ] {
+            writeln!(
+                f,
+                "{}: owner {}, size {}",
+                name,
+                if check.owner_is_solido_program { "ok" } else { "WRONG" },
+                if check.size_is_sufficient { "ok" } else { "TOO SMALL" },
+            )?;
+        }
+        writeln!(
+            f,
+            "Max commission percentage in range: {}",
+            self.max_commission_percentage_in_range
+        )?;
+        writeln!(f, "\nSimulation logs:")?;
+        for line in &self.simulation_logs {
+            writeln!(f, "  {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// CLI entry point to sanity-check a `migrate_state_to_v2` proposal before
+/// any multisig signer signs it.
+///
+/// This builds the exact same instruction `command_migrate_state_to_v2`
+/// would propose, but only simulates it read-only, and separately checks
+/// that the list accounts the signers were given actually look like what
+/// `command_create_v2_accounts` would have produced, so a bad address can't
+/// slip through a visual review of the proposal.
+pub fn command_verify_migration(
+    config: &mut SnapshotClientConfig,
+    opts: &MigrateStateToV2Opts,
+) -> solido_cli_common::Result<VerifyMigrationOutput> {
+    config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+        let old_reward_distribution = solido.reward_distribution.clone();
+
+        let (multisig_address, _) =
+            get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+        let new_reward_distribution = RewardDistribution {
+            treasury_fee: *opts.treasury_fee_share(),
+            developer_fee: *opts.developer_fee_share(),
+            st_sol_appreciation: *opts.st_sol_appreciation_share(),
+        };
+
+        let instruction = lido::instruction::migrate_state_to_v2(
+            opts.solido_program_id(),
+            new_reward_distribution.clone(),
+            *opts.min_block_production_rate(),
+            *opts.min_vote_success_rate(),
+            *opts.max_commission_percentage(),
+            &lido::instruction::MigrateStateToV2Meta {
+                lido: *opts.solido_address(),
+                manager: multisig_address,
+                validator_list: *opts.validator_list_address(),
+                validator_perf_list: *opts.validator_perf_list_address(),
+                maintainer_list: *opts.maintainer_list_address(),
+                developer_account: *opts.developer_fee_address(),
+            },
+        );
+
+        let validator_list_size = AccountList::<Validator>::required_bytes(V2_MAX_VALIDATORS);
+        let validator_perf_list_size =
+            AccountList::<ValidatorPerf>::required_bytes(V2_MAX_VALIDATORS);
+        let maintainer_list_size = AccountList::<Maintainer>::required_bytes(V2_MAX_MAINTAINERS);
+
+        let check_list_account = |address: &Pubkey, min_size: usize| -> solido_cli_common::Result<ListAccountCheck> {
+            let account = config.client.get_account(address)?;
+            Ok(ListAccountCheck {
+                address: *address,
+                owner_is_solido_program: account.owner == *opts.solido_program_id(),
+                size_is_sufficient: account.data.len() >= min_size,
+            })
+        };
+
+        let validator_list =
+            check_list_account(opts.validator_list_address(), validator_list_size)?;
+        let validator_perf_list =
+            check_list_account(opts.validator_perf_list_address(), validator_perf_list_size)?;
+        let maintainer_list =
+            check_list_account(opts.maintainer_list_address(), maintainer_list_size)?;
+
+        let max_commission_percentage_in_range = *opts.max_commission_percentage() <= 100;
+
+        let message = Message::new(&[instruction], Some(&config.signer.pubkey()));
+        let simulation_logs = config.client.simulate_transaction(&message)?;
+
+        Ok(VerifyMigrationOutput {
+            old_reward_distribution,
+            new_reward_distribution,
+            validator_list,
+            validator_perf_list,
+            maintainer_list,
+            max_commission_percentage_in_range,
+            simulation_logs,
+        })
+    })
+}
@@ -80,3 +80,20 @@ impl ListEntry for Maintainer {
         &self.pubkey
     }
 }
+
+/// Closure-based counterpart to `BigVec::find`, which only supports a fixed
+/// `fn(&[u8], &[u8]) -> bool` byte-level predicate.
+///
+/// Callers that already hold a deserialized `&[T]` (e.g. `AccountList::entries`)
+/// can use this to query on typed fields directly instead of writing a
+/// one-off byte comparator per query — e.g. the first `AcceptingStakes`
+/// validator with the lowest `effective_stake_balance`, or a validator whose
+/// `unstake_accounts_balance` is non-zero.
+pub fn find<T, F: Fn(&T) -> bool>(entries: &[T], predicate: F) -> Option<&T> {
+    entries.iter().find(|entry| predicate(entry))
+}
+
+/// Mutable counterpart to `find`.
+pub fn find_mut<T, F: Fn(&T) -> bool>(entries: &mut [T], predicate: F) -> Option<&mut T> {
+    entries.iter_mut().find(|entry| predicate(entry))
+}
@@ -11,12 +11,18 @@ use solana_program::{
 };
 
 use crate::error::LidoError;
+use crate::fraction::{from_percentage, per64};
 use crate::processor::StakeType;
 use crate::state::{AccountType, ListEntry, SeedRange};
 use crate::token::Lamports;
 use crate::util::serialize_b58;
 use crate::{VALIDATOR_STAKE_ACCOUNT, VALIDATOR_UNSTAKE_ACCOUNT};
 
+/// Upper bound on the vote-credit growth `compute_score` normalizes against,
+/// i.e. the number of slots in an epoch, since a validator cannot earn more
+/// than one vote credit per slot.
+const MAX_EPOCH_VOTE_CREDITS: u64 = solana_program::clock::DEFAULT_SLOTS_PER_EPOCH;
+
 /// How well the pool accepts a certain validator.
 #[repr(i8)]
 #[derive(
@@ -33,6 +39,20 @@ pub enum ValidatorStatus {
     /// and once unstaking is complete, the validator should be removed.
     /// This status is irreversible.
     PendingRemoval = -1,
+
+    /// The validator was automatically suspended after its `strikes` count
+    /// crossed `Validator::MAX_STRIKES`, e.g. because its observed balance
+    /// decreased. New stakes are not accepted, the same as `StakesSuspended`,
+    /// but an operator must explicitly reactivate the validator; it does not
+    /// recover on its own the way a plain deactivation can.
+    ///
+    /// Appended last (rather than next to `StakesSuspended`) because
+    /// `ValidatorStatus` round-trips through Borsh via `try_from_slice`,
+    /// which encodes the on-chain tag as the variant's positional index and
+    /// ignores the `= -1` on `PendingRemoval`; inserting a variant earlier
+    /// in the list would shift every later tag and reinterpret already
+    /// stored validators as the wrong status.
+    Probation = 2,
 }
 
 impl Default for ValidatorStatus {
@@ -74,9 +94,30 @@ pub struct Validator {
     /// Controls if a validator is allowed to have new stake deposits.
     /// When removing a validator, this flag should be set to `false`.
     pub status: ValidatorStatus,
+
+    /// The validator's most recently observed commission, in percent.
+    /// Used by `compute_score` to favor lower-commission validators.
+    pub commission: u8,
+
+    /// The validator vote account's `epoch_credits` total as of the
+    /// previous observation, used together with `epoch_credits_observed`
+    /// to derive the vote-credit growth for `compute_score`.
+    pub last_vote_credits: u64,
+
+    /// The validator vote account's `epoch_credits` total as of the most
+    /// recent observation.
+    pub epoch_credits_observed: u64,
+
+    /// Number of times `observe_balance` has detected a balance decrease for
+    /// this validator. Reset when an operator reactivates the validator.
+    pub strikes: u8,
 }
 
 impl Validator {
+    /// Number of strikes a validator can accumulate, via `record_strike`,
+    /// before it is automatically put on `ValidatorStatus::Probation`.
+    pub const MAX_STRIKES: u8 = 3;
+
     /// Return the balance in only the stake accounts, excluding the unstake accounts.
     pub fn compute_effective_stake_balance(&self) -> Lamports {
         (self.stake_accounts_balance - self.unstake_accounts_balance)
@@ -97,6 +138,81 @@ impl Validator {
         Ok(())
     }
 
+    /// Record a strike against this validator, e.g. after `observe_balance`
+    /// detected a balance decrease, and suspend new stake deposits once
+    /// `MAX_STRIKES` is crossed.
+    ///
+    /// Modeled on the slashing/penalty bookkeeping in the Substrate staking
+    /// module: rather than relying solely on a hard abort, misbehaving or
+    /// buggy validators are automatically excluded from new deposits, while
+    /// still recording the reason on-chain as `ValidatorStatus::Probation`.
+    pub fn record_strike(&mut self) {
+        self.strikes = self.strikes.saturating_add(1);
+        if self.strikes >= Self::MAX_STRIKES && self.status == ValidatorStatus::AcceptingStakes {
+            msg!(
+                "Validator {} has accumulated {} strikes, putting it on probation.",
+                self.vote_account_address,
+                self.strikes
+            );
+            self.status = ValidatorStatus::Probation;
+        }
+    }
+
+    /// True only if the validator has been put on probation automatically,
+    /// and is waiting for an operator to explicitly reactivate it.
+    pub fn is_on_probation(&self) -> bool {
+        self.status == ValidatorStatus::Probation
+    }
+
+    /// Explicitly bring a validator back from probation, clearing its
+    /// accumulated strikes. Unlike `activate`, this is the only way out of
+    /// `ValidatorStatus::Probation`, since that status is not expected to
+    /// recover on its own.
+    pub fn reactivate_from_probation(&mut self) {
+        if self.status != ValidatorStatus::Probation {
+            msg!("Validator is {:?}, so not reactivating ...", self.status);
+            return;
+        }
+
+        self.strikes = 0;
+        self.status = ValidatorStatus::AcceptingStakes;
+    }
+
+    /// Fold `commission` and this epoch's vote-credit growth into a single
+    /// `per64` weight that values low-commission, high-growth validators
+    /// more, for weight-driven rebalancing.
+    ///
+    /// Modeled on the validator-scoring idea from the Substrate staking
+    /// module: commission is inverted (lower is better) and blended evenly
+    /// with the raw vote-credit growth since `last_vote_credits`, so the
+    /// weight grows both as a validator lowers its commission and as it
+    /// produces more vote credits per epoch.
+    pub fn compute_score(&self) -> u64 {
+        let commission_score = from_percentage(100u8.saturating_sub(self.commission));
+        let credit_growth = self
+            .epoch_credits_observed
+            .saturating_sub(self.last_vote_credits)
+            .min(MAX_EPOCH_VOTE_CREDITS);
+        let credit_score = per64(credit_growth, MAX_EPOCH_VOTE_CREDITS);
+
+        ((commission_score as u128 + credit_score as u128) / 2) as u64
+    }
+
+    /// Desired `effective_stake_balance` for this validator under
+    /// weight-driven rebalancing: its proportional share of
+    /// `total_active_stake`, weighted by `compute_score` relative to
+    /// `sum_of_scores`, the sum of `compute_score()` across all validators
+    /// competing for stake.
+    pub fn target_lamports(&self, total_active_stake: Lamports, sum_of_scores: u64) -> Lamports {
+        if sum_of_scores == 0 {
+            return Lamports(0);
+        }
+        Lamports(
+            ((total_active_stake.0 as u128) * (self.compute_score() as u128)
+                / sum_of_scores as u128) as u64,
+        )
+    }
+
     pub fn has_stake_accounts(&self) -> bool {
         self.stake_seeds.begin != self.stake_seeds.end
     }
@@ -168,11 +284,23 @@ impl Validator {
         seed: u64,
         stake_type: StakeType,
     ) -> (Pubkey, u8) {
-        let authority = match stake_type {
-            StakeType::Stake => VALIDATOR_STAKE_ACCOUNT,
-            StakeType::Unstake => VALIDATOR_UNSTAKE_ACCOUNT,
-        };
-        self.find_stake_account_address_with_authority(program_id, solido_account, authority, seed)
+        match stake_type {
+            StakeType::Stake => self.find_stake_account_address_with_authority(
+                program_id,
+                solido_account,
+                VALIDATOR_STAKE_ACCOUNT,
+                seed,
+            ),
+            StakeType::Unstake => self.find_stake_account_address_with_authority(
+                program_id,
+                solido_account,
+                VALIDATOR_UNSTAKE_ACCOUNT,
+                seed,
+            ),
+            StakeType::EphemeralUnstake(epoch) => {
+                self.find_ephemeral_unstake_account_address(program_id, solido_account, seed, epoch)
+            }
+        }
     }
 
     /// Get stake account address that should be merged into another right after creation.
@@ -191,6 +319,27 @@ impl Validator {
         self.find_stake_account_address_with_authority(program_id, solido_account, &authority, seed)
     }
 
+    /// Get an unstake account address scoped to `epoch`, following the same
+    /// pattern as `find_temporary_stake_account_address`: concatenating
+    /// `VALIDATOR_UNSTAKE_ACCOUNT` with the epoch's bytes so each epoch gets
+    /// a fresh generation of unstake accounts.
+    ///
+    /// Unlike the long-lived `unstake_seeds` range, these are meant to be
+    /// split off and destroyed within a single transaction, so a maintainer
+    /// can redelegate or decrease stake without colliding with another
+    /// concurrent same-epoch operation, and without growing the permanent
+    /// unstake range that must be iterated during updates.
+    pub fn find_ephemeral_unstake_account_address(
+        &self,
+        program_id: &Pubkey,
+        solido_account: &Pubkey,
+        seed: u64,
+        epoch: Epoch,
+    ) -> (Pubkey, u8) {
+        let authority = [VALIDATOR_UNSTAKE_ACCOUNT, &epoch.to_le_bytes()[..]].concat();
+        self.find_stake_account_address_with_authority(program_id, solido_account, &authority, seed)
+    }
+
     /// True only if the validator is accepting new stake.
     pub fn is_active(&self) -> bool {
         self.status == ValidatorStatus::AcceptingStakes
@@ -232,7 +381,10 @@ impl Validator {
 impl Sealed for Validator {}
 
 impl Pack for Validator {
-    const LEN: usize = 89;
+    // 89 bytes for the original fields, plus 1 byte for `commission`, 8
+    // bytes each for `last_vote_credits` and `epoch_credits_observed`, and
+    // 1 byte for `strikes`.
+    const LEN: usize = 107;
     fn pack_into_slice(&self, data: &mut [u8]) {
         let mut data = data;
         BorshSerialize::serialize(&self, &mut data).unwrap();
@@ -253,6 +405,10 @@ impl Default for Validator {
             effective_stake_balance: Lamports(0),
             vote_account_address: Pubkey::default(),
             status: ValidatorStatus::default(),
+            commission: 0,
+            last_vote_credits: 0,
+            epoch_credits_observed: 0,
+            strikes: 0,
         }
     }
 }
@@ -271,3 +427,161 @@ impl ListEntry for Validator {
         &self.vote_account_address
     }
 }
+
+/// Split `total` Lamports across recipients proportionally to `weights`
+/// (`per64`-encoded, not required to sum to `u64::MAX`), losslessly.
+///
+/// Computing `target_lamports` independently per validator and rounding
+/// each share down would either lose Lamports to rounding or, if rounded
+/// up, double-count them across validators, treasury, and developer fee
+/// recipients. This implements the largest-remainder (Hamilton) method
+/// instead: take the floor of `total * weights[i] / sum(weights)` as the
+/// base share for each recipient, then hand one extra Lamport to the
+/// recipients with the largest remainders, in order, until the leftover
+/// from the floor divisions is exhausted. Ties are broken by lowest index.
+/// When `weights` is empty or sums to zero, every share is zero; otherwise
+/// the returned shares sum to exactly `total`.
+pub fn distribute(total: Lamports, weights: &[u64]) -> Vec<Lamports> {
+    let weight_sum: u128 = weights.iter().map(|&w| w as u128).sum();
+    if weight_sum == 0 {
+        return vec![Lamports(0); weights.len()];
+    }
+
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    let mut distributed: u128 = 0;
+    for &weight in weights {
+        let product = total.0 as u128 * weight as u128;
+        let share = product / weight_sum;
+        let remainder = product % weight_sum;
+        distributed += share;
+        shares.push(share);
+        remainders.push(remainder);
+    }
+
+    let leftover = (total.0 as u128).saturating_sub(distributed) as usize;
+    let mut indices: Vec<usize> = (0..weights.len()).collect();
+    indices.sort_by(|&a, &b| remainders[b].cmp(&remainders[a]).then(a.cmp(&b)));
+    for &i in indices.iter().take(leftover) {
+        shares[i] += 1;
+    }
+
+    shares.into_iter().map(|share| Lamports(share as u64)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn validator_with(
+        commission: u8,
+        last_vote_credits: u64,
+        epoch_credits_observed: u64,
+    ) -> Validator {
+        Validator {
+            commission,
+            last_vote_credits,
+            epoch_credits_observed,
+            ..Default::default()
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn compute_score_is_monotonic_in_commission(
+            commission_low in 0u8..=100,
+            commission_high in 0u8..=100,
+            credits in 0u64..=(MAX_EPOCH_VOTE_CREDITS * 2),
+        ) {
+            prop_assume!(commission_low <= commission_high);
+            let low_commission = validator_with(commission_low, 0, credits);
+            let high_commission = validator_with(commission_high, 0, credits);
+            // Lower commission must never score lower than higher commission,
+            // holding credit growth fixed.
+            prop_assert!(low_commission.compute_score() >= high_commission.compute_score());
+        }
+
+        #[test]
+        fn compute_score_is_monotonic_in_credit_growth(
+            commission in 0u8..=100,
+            credits_low in 0u64..=MAX_EPOCH_VOTE_CREDITS,
+            extra_credits in 0u64..=MAX_EPOCH_VOTE_CREDITS,
+        ) {
+            let credits_high = credits_low + extra_credits;
+            let low_growth = validator_with(commission, 0, credits_low);
+            let high_growth = validator_with(commission, 0, credits_high);
+            // More vote-credit growth must never score lower, holding commission fixed.
+            prop_assert!(high_growth.compute_score() >= low_growth.compute_score());
+        }
+
+        #[test]
+        fn target_lamports_never_exceeds_total(
+            commission in 0u8..=100,
+            last_vote_credits in 0u64..=MAX_EPOCH_VOTE_CREDITS,
+            epoch_credits_observed in 0u64..=(MAX_EPOCH_VOTE_CREDITS * 2),
+            total in 0u64..=u64::MAX,
+            extra_score in 0u64..=u64::MAX,
+        ) {
+            let validator = validator_with(commission, last_vote_credits, epoch_credits_observed);
+            // `sum_of_scores` always covers at least this validator's own score,
+            // so its proportional share can never exceed the whole.
+            let sum_of_scores = validator.compute_score().saturating_add(extra_score).max(1);
+            let target = validator.target_lamports(Lamports(total), sum_of_scores);
+            prop_assert!(target.0 <= total);
+        }
+
+        #[test]
+        fn target_lamports_is_monotonic_in_score(
+            commission_low in 0u8..=100,
+            commission_high in 0u8..=100,
+            last_vote_credits in 0u64..=MAX_EPOCH_VOTE_CREDITS,
+            epoch_credits_observed in 0u64..=(MAX_EPOCH_VOTE_CREDITS * 2),
+            total in 1u64..=1_000_000_000u64,
+        ) {
+            prop_assume!(commission_low <= commission_high);
+            let low_commission = validator_with(commission_low, last_vote_credits, epoch_credits_observed);
+            let high_commission = validator_with(commission_high, last_vote_credits, epoch_credits_observed);
+            let sum_of_scores = low_commission
+                .compute_score()
+                .max(high_commission.compute_score())
+                .max(1)
+                * 2;
+            // Lower commission scores at least as high, so under a shared
+            // `sum_of_scores` it must be allotted at least as many Lamports.
+            prop_assert!(
+                low_commission.target_lamports(Lamports(total), sum_of_scores).0
+                    >= high_commission.target_lamports(Lamports(total), sum_of_scores).0
+            );
+        }
+
+        #[test]
+        fn distribute_sums_to_total(
+            total in 0u64..=u64::MAX,
+            weights in prop::collection::vec(0u64..=1_000_000, 1..20),
+        ) {
+            let shares = distribute(Lamports(total), &weights);
+            let sum: u128 = shares.iter().map(|share| share.0 as u128).sum();
+            prop_assert_eq!(sum, total as u128);
+        }
+
+        #[test]
+        fn distribute_is_monotonic_in_weights(
+            total in 0u64..=1_000_000_000u64,
+            weights in prop::collection::vec(1u64..=1_000_000, 2..20),
+            index in 0usize..20,
+            extra_weight in 0u64..=1_000_000,
+        ) {
+            let index = index % weights.len();
+            let mut increased_weights = weights.clone();
+            increased_weights[index] += extra_weight;
+
+            let shares = distribute(Lamports(total), &weights);
+            let increased_shares = distribute(Lamports(total), &increased_weights);
+
+            // Raising one recipient's weight, holding the others fixed, must
+            // never decrease that recipient's share.
+            prop_assert!(increased_shares[index].0 >= shares[index].0);
+        }
+    }
+}
@@ -27,6 +27,44 @@ pub struct Criteria {
 
     /// If a validator has `vote_success_rate` lower than this, then it gets deactivated.
     pub min_vote_success_rate: u64,
+
+    /// Amount added to `inactivity_score` for an epoch that misses the thresholds above.
+    ///
+    /// While this is nonzero, missing `min_block_production_rate` or
+    /// `min_vote_success_rate` deactivates a validator only through
+    /// `inactivity_score`; while it is zero, a miss deactivates it directly.
+    pub inactivity_penalty: u64,
+
+    /// Amount subtracted from `inactivity_score` for an epoch that meets the thresholds above.
+    pub inactivity_recovery: u64,
+
+    /// Once `inactivity_score` reaches this value, the validator gets deactivated.
+    /// The validator is only accepted back once its `inactivity_score` has
+    /// bled back down to zero.
+    pub inactivity_deactivation_threshold: u64,
+
+    /// Weight of the (inverted) commission in `ValidatorPerf::score`.
+    pub weight_commission: u32,
+
+    /// Weight of the block production rate in `ValidatorPerf::score`.
+    pub weight_block_production: u32,
+
+    /// Weight of the vote success rate in `ValidatorPerf::score`.
+    pub weight_vote_success: u32,
+
+    /// If a validator's blended `ValidatorPerf::score` is at least this, then
+    /// it meets the criteria even when it misses the per-metric cutoffs above.
+    /// A score below this does not deactivate a validator that clears those
+    /// cutoffs. `0` disables this route.
+    pub min_total_score: u32,
+
+    /// If a validator's `data_center_stake_concentration` (in basis points of
+    /// total network stake) exceeds this, then it gets deactivated.
+    pub max_data_center_concentration: u64,
+
+    /// If true, validators with `in_superminority` set get deactivated,
+    /// regardless of their other metrics.
+    pub exclude_superminority: bool,
 }
 
 impl Default for Criteria {
@@ -35,6 +73,15 @@ impl Default for Criteria {
             max_commission: 100,
             min_vote_success_rate: 0,
             min_block_production_rate: 0,
+            inactivity_penalty: 0,
+            inactivity_recovery: 0,
+            inactivity_deactivation_threshold: u64::MAX,
+            weight_commission: 1,
+            weight_block_production: 1,
+            weight_vote_success: 1,
+            min_total_score: 0,
+            max_data_center_concentration: u64::MAX,
+            exclude_superminority: false,
         }
     }
 }
@@ -49,6 +96,7 @@ impl Criteria {
             max_commission,
             min_vote_success_rate,
             min_block_production_rate,
+            ..Default::default()
         }
     }
 }
@@ -71,6 +119,25 @@ pub struct OffchainValidatorPerf {
 
     /// Ratio of successful votes to total votes.
     pub vote_success_rate: u64,
+
+    /// Accumulated inactivity, bounded by `Criteria::inactivity_deactivation_threshold`.
+    ///
+    /// Grows by `Criteria::inactivity_penalty` every epoch the raw metrics above
+    /// miss the thresholds, and shrinks by `Criteria::inactivity_recovery` every
+    /// epoch they are met, so a single bad epoch does not immediately flip
+    /// `ValidatorPerf::meets_criteria`.
+    pub inactivity_score: u64,
+
+    /// Share of total network stake concentrated in this validator's data
+    /// center, in basis points. Supplied by the maintainer, since the
+    /// program has no way to observe topology on its own.
+    pub data_center_stake_concentration: u64,
+
+    /// Whether this validator sits in the superminority, i.e. whether it is
+    /// among the smallest set of validators that together control enough
+    /// stake to halt the network. Supplied by the maintainer, for the same
+    /// reason as `data_center_stake_concentration`.
+    pub in_superminority: bool,
 }
 
 /// NOTE: ORDER IS VERY IMPORTANT HERE, PLEASE DO NOT RE-ORDER THE FIELDS UNLESS
@@ -97,25 +164,149 @@ pub struct ValidatorPerf {
 
     /// The off-chain part of the validator's performance, if available.
     pub rest: Option<OffchainValidatorPerf>,
+
+    /// The highest `commission` observed since `commission_updated_at` last
+    /// started a new epoch window.
+    ///
+    /// `commission` alone only reflects the value at the moment of the last
+    /// snapshot, so a validator could raise its commission right after being
+    /// observed and lower it again before the next observation, without ever
+    /// tripping `Criteria::max_commission`. `max_commission_observed` instead
+    /// never decreases within a window, so any spike during the epoch is caught.
+    pub max_commission_observed: u8,
+}
+
+/// Normalize a per64-encoded fraction (`0..=u64::MAX` representing `0%..=100%`)
+/// to a `0..=100` score.
+fn normalize_rate_to_score(rate: u64) -> u32 {
+    ((rate as u128) * 100 / u64::MAX as u128) as u32
 }
 
 impl ValidatorPerf {
+    /// Record a freshly observed commission, folding it into the high-water
+    /// mark for the current epoch window, and starting a new window if
+    /// `current_epoch` differs from `commission_updated_at`.
+    pub fn update_commission(&mut self, new_commission: u8, current_epoch: Epoch) {
+        if self.commission_updated_at == current_epoch {
+            self.max_commission_observed = self.max_commission_observed.max(new_commission);
+        } else {
+            self.max_commission_observed = new_commission;
+        }
+        self.commission = new_commission;
+        self.commission_updated_at = current_epoch;
+    }
+
     /// True only if these metrics meet the criteria.
+    ///
+    /// A validator passes either by clearing every per-metric cutoff, or by
+    /// clearing the blended `score` threshold as an alternative route, per
+    /// `Criteria::min_total_score`'s doc comment. With `min_total_score == 0`
+    /// (the default) that alternative route is skipped, since every score
+    /// would clear a zero threshold; it only starts mattering once configured.
+    /// `exclude_superminority` is not part of either route: it is a hard
+    /// exclusion that applies regardless of the other metrics or the score.
     pub fn meets_criteria(&self, criteria: &Criteria) -> bool {
-        self.commission <= criteria.max_commission
+        let excluded_for_superminority = self
+            .rest
+            .as_ref()
+            .map_or(false, |perf| criteria.exclude_superminority && perf.in_superminority);
+        if excluded_for_superminority {
+            return false;
+        }
+
+        let meets_per_metric_cutoffs = self.max_commission_observed <= criteria.max_commission
             && self.rest.as_ref().map_or(true, |perf| {
-                perf.vote_success_rate >= criteria.min_vote_success_rate
-                    && perf.block_production_rate >= criteria.min_block_production_rate
-            })
+                // With a nonzero `inactivity_penalty`, missed rates only count
+                // through `inactivity_score`, so a single bad epoch is absorbed.
+                let meets_rates = criteria.inactivity_penalty > 0
+                    || (perf.vote_success_rate >= criteria.min_vote_success_rate
+                        && perf.block_production_rate >= criteria.min_block_production_rate);
+                meets_rates
+                    && perf.inactivity_score < criteria.inactivity_deactivation_threshold
+                    && perf.data_center_stake_concentration <= criteria.max_data_center_concentration
+            });
+        let meets_min_total_score =
+            criteria.min_total_score > 0 && self.score(criteria) >= criteria.min_total_score;
+
+        meets_per_metric_cutoffs || meets_min_total_score
     }
-}
 
-impl ValidatorPerf {}
+    /// Blend commission, block production, and vote success into a single
+    /// `0..=100` quality score, weighted by `criteria`'s `weight_*` fields.
+    ///
+    /// Commission is inverted (lower commission is better), and the two
+    /// off-chain rates default to a perfect score when no reading has been
+    /// collected yet, so a validator is not penalized before its first
+    /// off-chain update. This lets the off-chain maintainer rank validators
+    /// by quality and concentrate stake toward the highest scorers, instead
+    /// of treating every validator above the per-metric cutoffs as equal.
+    pub fn score(&self, criteria: &Criteria) -> u32 {
+        let commission_score = 100u32.saturating_sub(self.commission as u32);
+        let (block_production_score, vote_success_score) = self.rest.as_ref().map_or(
+            (100, 100),
+            |perf| {
+                (
+                    normalize_rate_to_score(perf.block_production_rate),
+                    normalize_rate_to_score(perf.vote_success_rate),
+                )
+            },
+        );
+
+        let total_weight = (criteria.weight_commission
+            + criteria.weight_block_production
+            + criteria.weight_vote_success) as u64;
+        if total_weight == 0 {
+            return 100;
+        }
+
+        let weighted_sum = commission_score as u64 * criteria.weight_commission as u64
+            + block_production_score as u64 * criteria.weight_block_production as u64
+            + vote_success_score as u64 * criteria.weight_vote_success as u64;
+
+        (weighted_sum / total_weight) as u32
+    }
+
+    /// True only if the validator has fully recovered from past inactivity,
+    /// i.e. its `inactivity_score` has bled all the way back down to zero.
+    ///
+    /// `reactivate_if_complies` requires this in addition to `meets_criteria`,
+    /// so a validator cannot be brought back while it is still working off a
+    /// penalty from a previous bout of flakiness.
+    pub fn has_recovered_from_inactivity(&self) -> bool {
+        self.rest
+            .as_ref()
+            .map_or(true, |perf| perf.inactivity_score == 0)
+    }
+
+    /// Recompute `inactivity_score` after a new epoch's off-chain metrics are committed.
+    ///
+    /// Call this with the just-updated `rest` readings: the score decays by
+    /// `inactivity_recovery` when the raw metrics meet the thresholds, and
+    /// otherwise grows by `inactivity_penalty`, capped at
+    /// `inactivity_deactivation_threshold` so it cannot run away unbounded.
+    pub fn update_inactivity_score(&mut self, criteria: &Criteria) {
+        if let Some(perf) = self.rest.as_mut() {
+            let meets_raw_metrics = perf.vote_success_rate >= criteria.min_vote_success_rate
+                && perf.block_production_rate >= criteria.min_block_production_rate;
+            perf.inactivity_score = if meets_raw_metrics {
+                perf.inactivity_score
+                    .saturating_sub(criteria.inactivity_recovery)
+            } else {
+                (perf.inactivity_score + criteria.inactivity_penalty)
+                    .min(criteria.inactivity_deactivation_threshold)
+            };
+        }
+    }
+}
 
 impl Sealed for ValidatorPerf {}
 
 impl Pack for ValidatorPerf {
-    const LEN: usize = 64;
+    // 64 bytes for the original fields, plus 8 bytes for the `inactivity_score`
+    // that was added to `OffchainValidatorPerf`, plus 1 byte for
+    // `max_commission_observed`, plus 8 bytes for
+    // `data_center_stake_concentration` and 1 byte for `in_superminority`.
+    const LEN: usize = 82;
     fn pack_into_slice(&self, data: &mut [u8]) {
         let mut data = data;
         BorshSerialize::serialize(&self, &mut data).unwrap();
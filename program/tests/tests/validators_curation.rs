@@ -67,7 +67,7 @@ async fn test_curate_by_min_block_production_rate() {
 
     // And when the validator's block production rate for the epoch is observed:
     let result = context
-        .try_update_offchain_validator_perf(*validator.pubkey(), 98, 0)
+        .try_update_offchain_validator_perf(*validator.pubkey(), 98, 0, 0, false)
         .await;
     assert!(result.is_ok());
 
@@ -101,7 +101,7 @@ async fn test_curate_by_min_vote_success_rate() {
 
     // And when the validator's vote success rate for the epoch is observed:
     let result = context
-        .try_update_offchain_validator_perf(*validator.pubkey(), 0, 98)
+        .try_update_offchain_validator_perf(*validator.pubkey(), 0, 98, 0, false)
         .await;
     assert!(result.is_ok());
 
@@ -126,7 +126,7 @@ async fn test_update_block_production_rate() {
 
     // When an epoch passes, and the validator's block production rate is observed:
     let result = context
-        .try_update_offchain_validator_perf(*validator.pubkey(), 98, 0)
+        .try_update_offchain_validator_perf(*validator.pubkey(), 98, 0, 0, false)
         .await;
     assert!(result.is_ok());
 
@@ -154,7 +154,7 @@ async fn test_update_vote_success_rate() {
 
     // When an epoch passes, and the validator's vote success rate is observed:
     let result = context
-        .try_update_offchain_validator_perf(*validator.pubkey(), 0, 98)
+        .try_update_offchain_validator_perf(*validator.pubkey(), 0, 98, 0, false)
         .await;
     assert!(result.is_ok());
 
@@ -182,13 +182,13 @@ async fn test_perf_updates_at_most_once_per_epoch() {
 
     // When the uptime of a validator gets updated:
     let result = context
-        .try_update_offchain_validator_perf(*validator.pubkey(), 98, 0)
+        .try_update_offchain_validator_perf(*validator.pubkey(), 98, 0, 0, false)
         .await;
     assert!(result.is_ok());
 
     // And when the uptime of the same validator gets updated again in the same epoch:
     let result = context
-        .try_update_offchain_validator_perf(*validator.pubkey(), 99, 0)
+        .try_update_offchain_validator_perf(*validator.pubkey(), 99, 0, 0, false)
         .await;
 
     // Then the second update fails:
@@ -199,7 +199,7 @@ async fn test_perf_updates_at_most_once_per_epoch() {
 
     // Then the second update succeeds:
     let result = context
-        .try_update_offchain_validator_perf(*validator.pubkey(), 99, 0)
+        .try_update_offchain_validator_perf(*validator.pubkey(), 99, 0, 0, false)
         .await;
     assert!(result.is_ok());
 }
@@ -220,7 +220,7 @@ async fn test_bring_back() {
     assert!(result.is_ok());
 
     let result = context
-        .try_update_offchain_validator_perf(*validator.pubkey(), 98, 0)
+        .try_update_offchain_validator_perf(*validator.pubkey(), 98, 0, 0, false)
         .await;
     assert!(result.is_ok());
 
@@ -235,7 +235,7 @@ async fn test_bring_back() {
 
     // And when the validator's performance is back to normal:
     let result = context
-        .try_update_offchain_validator_perf(*validator.pubkey(), 101, 0)
+        .try_update_offchain_validator_perf(*validator.pubkey(), 101, 0, 0, false)
         .await;
     assert!(result.is_ok());
 
@@ -248,6 +248,116 @@ async fn test_bring_back() {
     assert!(validator.is_active());
 }
 
+#[tokio::test]
+async fn test_inactivity_score_survives_a_single_bad_epoch() {
+    // Given a Solido context and an active validator:
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    context.advance_to_normal_epoch(0);
+    let validator = &context.get_solido().await.validators.entries[0];
+
+    // When Solido imposes a minimum block production rate with hysteresis:
+    let result = context
+        .try_change_criteria(&Criteria {
+            min_block_production_rate: 99,
+            inactivity_penalty: 1,
+            inactivity_recovery: 1,
+            inactivity_deactivation_threshold: 2,
+            ..context.criteria
+        })
+        .await;
+    assert!(result.is_ok());
+
+    // And a single epoch dips below the threshold:
+    let result = context
+        .try_update_offchain_validator_perf(*validator.pubkey(), 98, 0, 0, false)
+        .await;
+    assert!(result.is_ok());
+
+    let result = context
+        .try_deactivate_if_violates(*validator.pubkey())
+        .await;
+    assert!(result.is_ok());
+
+    // Then the validator is not deactivated yet, because one flap is not
+    // enough to cross `inactivity_deactivation_threshold`:
+    let validator = &context.get_solido().await.validators.entries[0];
+    assert!(validator.is_active());
+}
+
+#[tokio::test]
+async fn test_inactivity_score_deactivates_after_repeated_flapping() {
+    // Given a Solido context and an active validator:
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    context.advance_to_normal_epoch(0);
+    let validator = &context.get_solido().await.validators.entries[0];
+
+    let result = context
+        .try_change_criteria(&Criteria {
+            min_block_production_rate: 99,
+            inactivity_penalty: 1,
+            inactivity_recovery: 1,
+            inactivity_deactivation_threshold: 2,
+            ..context.criteria
+        })
+        .await;
+    assert!(result.is_ok());
+
+    // When the validator misses the threshold for two epochs in a row:
+    for epoch in 0..2 {
+        let result = context
+            .try_update_offchain_validator_perf(*validator.pubkey(), 98, 0, 0, false)
+            .await;
+        assert!(result.is_ok());
+        context.advance_to_normal_epoch(epoch + 1);
+    }
+
+    let result = context
+        .try_deactivate_if_violates(*validator.pubkey())
+        .await;
+    assert!(result.is_ok());
+
+    // Then the accumulated inactivity score has crossed the threshold, and
+    // the validator is deactivated:
+    let validator = &context.get_solido().await.validators.entries[0];
+    assert!(!validator.is_active());
+}
+
+#[tokio::test]
+async fn test_curate_by_max_commission_observed_spike() {
+    // Given a Solido context and an active validator:
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    context.advance_to_normal_epoch(0);
+    let validator_accounts = context.validator.as_ref().unwrap();
+    let vote_account = validator_accounts.vote_account;
+    let withdraw_authority =
+        Keypair::from_bytes(&validator_accounts.withdraw_authority.to_bytes()).unwrap();
+
+    // When the validator raises its commission above the max, and the
+    // maintainer observes it while the spike is still live, folding it into
+    // this epoch's high-water mark:
+    let max_commission = context.criteria.max_commission;
+    context
+        .set_vote_account_commission(vote_account, &withdraw_authority, max_commission + 1)
+        .await;
+    context
+        .update_onchain_validator_perf_commission(vote_account)
+        .await;
+
+    // And the validator lowers it again before the next epoch:
+    context
+        .set_vote_account_commission(vote_account, &withdraw_authority, max_commission)
+        .await;
+
+    // Then the high-water mark for the epoch still reflects the spike, since
+    // `update_commission` only resets it on an epoch change, so curation must
+    // still be able to catch it:
+    let result = context.try_deactivate_if_violates(vote_account).await;
+    assert!(result.is_ok());
+
+    let validator = &context.get_solido().await.validators.entries[0];
+    assert!(!validator.is_active());
+}
+
 #[tokio::test]
 async fn test_close_vote_account() {
     let mut context = Context::new_with_maintainer_and_validator().await;
@@ -7,25 +7,34 @@ use borsh::BorshSerialize;
 use num_traits::cast::FromPrimitive;
 use rand::prelude::StdRng;
 use rand::SeedableRng;
+use solana_address_lookup_table_program::instruction as lookup_table_instruction;
 use solana_program::program_pack::Pack;
 use solana_program::rent::Rent;
 use solana_program::stake::state::Stake;
 use solana_program::system_instruction;
 use solana_program::system_program;
 use solana_program::{borsh::try_from_slice_unchecked, sysvar};
-use solana_program::{clock::Clock, instruction::Instruction};
-use solana_program::{instruction::InstructionError, stake_history::StakeHistory};
+use solana_program::{
+    clock::{Clock, Slot},
+    instruction::Instruction,
+};
+use solana_program::{
+    instruction::InstructionError,
+    stake_history::{StakeHistory, StakeHistoryEntry},
+};
 use solana_program_test::{processor, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext};
-use solana_sdk::account::{from_account, Account};
+use solana_sdk::account::{from_account, Account, AccountSharedData};
 use solana_sdk::account_info::AccountInfo;
+use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+use solana_sdk::message::{v0, VersionedMessage};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
 use solana_sdk::transaction::TransactionError;
 use solana_sdk::transport;
 use solana_sdk::transport::TransportError;
 use solana_vote_program::vote_instruction;
-use solana_vote_program::vote_state::{VoteInit, VoteState};
+use solana_vote_program::vote_state::{VoteAuthorize, VoteInit, VoteState, VoteStateVersions};
 
 use lido::processor::StakeType;
 use lido::stake_account::StakeAccount;
@@ -97,6 +106,10 @@ pub struct Context {
     pub mint_authority: Pubkey,
 
     pub criteria: Criteria,
+
+    /// Simulated `StakeHistory`, written into the bank by
+    /// `advance_epoch_with_stake_history`.
+    pub simulated_stake_history: SimulatedStakeHistory,
 }
 
 pub struct ValidatorAccounts {
@@ -105,6 +118,72 @@ pub struct ValidatorAccounts {
     pub withdraw_authority: Keypair,
 }
 
+/// The fraction of `activating`/`deactivating` stake that becomes effective
+/// in a single epoch, mirroring the real runtime's warmup/cooldown rate.
+const WARMUP_COOLDOWN_RATE: f64 = 0.25;
+
+/// Tracks a simulated `StakeHistory` sysvar across epoch warps.
+///
+/// `solana-program-test` does not populate `StakeHistory` when warping
+/// slots directly, so without this, stake that Solido delegates appears
+/// either fully effective or not activating at all. `advance_epoch`
+/// reproduces the standard warmup/cooldown recurrence: the newly-effective
+/// amount in an epoch is never more than `remaining_activating`, and never
+/// more than `WARMUP_COOLDOWN_RATE` of the pool's total managed stake
+/// (`effective + activating + deactivating`), and symmetrically for
+/// cooldown.
+#[derive(Clone, Debug, Default)]
+pub struct SimulatedStakeHistory {
+    effective: u64,
+    activating: u64,
+    deactivating: u64,
+}
+
+impl SimulatedStakeHistory {
+    /// Record that `amount` lamports of new stake started activating this epoch.
+    pub fn activate(&mut self, amount: u64) {
+        self.activating += amount;
+    }
+
+    /// Record that `amount` lamports of already-effective stake started deactivating this epoch.
+    pub fn deactivate(&mut self, amount: u64) {
+        self.effective = self.effective.saturating_sub(amount);
+        self.deactivating += amount;
+    }
+
+    /// Move stake from `activating`/`deactivating` into `effective` at
+    /// `WARMUP_COOLDOWN_RATE`, and return the resulting entry.
+    fn advance_epoch(&mut self) -> StakeHistoryEntry {
+        // The real warmup/cooldown rate is bounded by the *cluster's* total
+        // active stake, not by the amount already effective in this one
+        // pool: bounding by `self.effective` alone means a pool's very
+        // first delegation, with `effective == 0`, could never activate at
+        // all. Use this pool's total managed stake as the rate's base
+        // instead, so a fresh delegation actually warms up over a few
+        // epochs rather than getting stuck at zero.
+        let total_stake = self.effective + self.activating + self.deactivating;
+
+        let newly_effective =
+            (((total_stake as f64) * WARMUP_COOLDOWN_RATE) as u64).min(self.activating);
+        self.activating -= newly_effective;
+        self.effective += newly_effective;
+
+        // `deactivate` already moved the deactivating amount out of
+        // `effective` when it was called, so cooldown here only retires
+        // `deactivating` itself; subtracting from `effective` again would
+        // double-count the same lamports.
+        let newly_retired =
+            (((total_stake as f64) * WARMUP_COOLDOWN_RATE) as u64).min(self.deactivating);
+        self.deactivating -= newly_retired;
+
+        StakeHistoryEntry {
+            effective: self.effective,
+            activating: self.activating,
+            deactivating: self.deactivating,
+        }
+    }
+}
+
 /// Sign and send a transaction with a fresh block hash.
 ///
 /// The payer always signs, but additional signers can be passed as well.
@@ -180,6 +259,58 @@ pub async fn send_transaction(
     result
 }
 
+/// Sign and send a v0 versioned transaction with a fresh block hash,
+/// resolving `instructions`' account keys through `lookup_tables`.
+///
+/// This mirrors `send_transaction`, including its nonce/fresh-blockhash
+/// behavior and `LidoError` decoding, but builds a compressed
+/// `VersionedMessage::V0` instead of a legacy message, so tests can exercise
+/// instructions that would otherwise exceed the legacy account limit.
+pub async fn send_versioned_transaction(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    additional_signers: Vec<&Keypair>,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> transport::Result<()> {
+    context.last_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&context.last_blockhash)
+        .await
+        .expect("Failed to get a new blockhash.");
+
+    let message = v0::Message::try_compile(
+        &context.payer.pubkey(),
+        instructions,
+        lookup_tables,
+        context.last_blockhash,
+    )
+    .expect("Failed to compile versioned message.");
+
+    let mut signers = additional_signers;
+    signers.push(&context.payer);
+    let transaction = VersionedTransaction::try_new(VersionedMessage::V0(message), &signers)
+        .expect("Failed to sign versioned transaction.");
+
+    let result = context.banks_client.process_transaction(transaction).await;
+
+    if let Err(TransportError::TransactionError(TransactionError::InstructionError(
+        _,
+        InstructionError::Custom(error_code),
+    ))) = result
+    {
+        println!("Transaction failed with InstructionError::Custom.");
+        match LidoError::from_u32(error_code) {
+            Some(err) => println!(
+                "If this error originated from Solido, it was this variant: {:?}",
+                err
+            ),
+            None => println!("This error is not a known Solido error."),
+        }
+    }
+
+    result
+}
+
 #[derive(PartialEq, Debug)]
 pub struct SolidoWithLists {
     pub lido: Lido,
@@ -247,6 +378,7 @@ impl Context {
             mint_authority,
             deterministic_keypair,
             criteria: Criteria::new(5, 0, 0),
+            simulated_stake_history: SimulatedStakeHistory::default(),
         };
 
         result.st_sol_mint = result.create_mint(result.mint_authority).await;
@@ -402,6 +534,81 @@ impl Context {
             .expect("Failed to warp to epoch.");
     }
 
+    /// Warp straight to `slot`, and return the resulting `Clock`.
+    ///
+    /// Refreshes `self.context.last_blockhash` afterwards, since a warp
+    /// invalidates the cached blockhash and a subsequent `send_transaction`
+    /// would otherwise fail with a stale-blockhash error.
+    pub async fn warp_to_slot(&mut self, slot: Slot) -> Clock {
+        self.context
+            .warp_to_slot(slot)
+            .expect("Failed to warp to slot.");
+        self.context.last_blockhash = self
+            .context
+            .banks_client
+            .get_new_latest_blockhash(&self.context.last_blockhash)
+            .await
+            .expect("Failed to get new blockhash after warp.");
+        self.get_clock().await
+    }
+
+    /// Warp to the first slot of the epoch following the current one, and
+    /// return the resulting `Clock`.
+    ///
+    /// Unlike `advance_to_normal_epoch`, this advances relative to the
+    /// clock's current epoch instead of an absolute epoch number counted
+    /// from the first normal slot, so it composes with whatever warping
+    /// already happened before it.
+    pub async fn advance_to_next_epoch(&mut self) -> Clock {
+        let epoch_schedule = self.context.genesis_config().epoch_schedule;
+        let clock = self.get_clock().await;
+        let next_epoch_start_slot = epoch_schedule.get_first_slot_in_epoch(clock.epoch + 1);
+        self.warp_to_slot(next_epoch_start_slot).await
+    }
+
+    /// Like `advance_to_normal_epoch`, but also maintains a simulated
+    /// `StakeHistory` sysvar, so `Stake::stake(clock, stake_history)`
+    /// resolves Solido's delegated stake accounts to realistic intermediate
+    /// values instead of either fully effective or fully inactive.
+    ///
+    /// Invariant: the amount that becomes effective in any single epoch
+    /// never exceeds the stake that was still `activating` (or
+    /// `deactivating`) going into that epoch.
+    pub async fn advance_epoch_with_stake_history(&mut self, epoch: u64) {
+        let current_clock_epoch = self.get_clock().await.epoch;
+        let solido = self.get_solido().await;
+
+        for validator in &solido.validators.entries {
+            for seed in &validator.stake_seeds {
+                let (stake_address, _) = validator.find_stake_account_address(
+                    &id(),
+                    &self.solido.pubkey(),
+                    seed,
+                    StakeType::Stake,
+                );
+                if let Some(account) = self.try_get_account(stake_address).await {
+                    if let Ok(stake) = lido::stake_account::deserialize_stake_account(&account.data)
+                    {
+                        if stake.delegation.activation_epoch == current_clock_epoch {
+                            self.simulated_stake_history.activate(stake.delegation.stake);
+                        }
+                        if stake.delegation.deactivation_epoch == current_clock_epoch {
+                            self.simulated_stake_history
+                                .deactivate(stake.delegation.stake);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut history = self.get_stake_history().await;
+        let entry = self.simulated_stake_history.advance_epoch();
+        history.add(current_clock_epoch, entry);
+        self.context.set_sysvar(&history);
+
+        self.advance_to_normal_epoch(epoch);
+    }
+
     /// Initialize a new SPL token mint, return its instance address.
     pub async fn create_mint(&mut self, mint_authority: Pubkey) -> Pubkey {
         let mint = self.deterministic_keypair.new_keypair();
@@ -518,15 +725,19 @@ impl Context {
             .expect("Failed to delegate stake.");
     }
 
-    /// Merge two stake accounts, outside of Solido.
+    /// Merge two stake accounts, outside of Solido, returning any merge
+    /// error to the caller instead of panicking.
     ///
-    /// The authorized staker and withdrawer must be the same for both accounts.
-    pub async fn merge_stake_accounts(
+    /// Exposed separately from `merge_stake_accounts` so tests can provoke
+    /// and assert on `MergeActivatedStake`/`MergeMismatch` (authority or
+    /// lockup mismatch, or one side still activating) instead of only
+    /// exercising the happy path.
+    pub async fn try_merge_stake_accounts(
         &mut self,
         source: Pubkey,
         destination: Pubkey,
         authorized_staker_withdrawer: &Keypair,
-    ) {
+    ) -> transport::Result<()> {
         use solana_program::stake::instruction as stake;
         let instructions = stake::merge(
             &destination,
@@ -539,7 +750,20 @@ impl Context {
             vec![authorized_staker_withdrawer],
         )
         .await
-        .expect("Failed to merge stake.");
+    }
+
+    /// Merge two stake accounts, outside of Solido.
+    ///
+    /// The authorized staker and withdrawer must be the same for both accounts.
+    pub async fn merge_stake_accounts(
+        &mut self,
+        source: Pubkey,
+        destination: Pubkey,
+        authorized_staker_withdrawer: &Keypair,
+    ) {
+        self.try_merge_stake_accounts(source, destination, authorized_staker_withdrawer)
+            .await
+            .expect("Failed to merge stake.");
     }
 
     /// Deactivate a stake account, outside of Solido.
@@ -555,6 +779,90 @@ impl Context {
             .expect("Failed to deactivate stake.");
     }
 
+    /// Split a stake account into two, outside of Solido, moving `lamports`
+    /// into a freshly created account. Returns the new account's address.
+    pub async fn split_stake_account(
+        &mut self,
+        source: Pubkey,
+        lamports: Lamports,
+        authorized_staker_withdrawer: &Keypair,
+    ) -> Pubkey {
+        use solana_program::stake::instruction as stake;
+        use solana_program::stake::state::StakeState;
+
+        let destination = self.deterministic_keypair.new_keypair();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let stake_state_len = std::mem::size_of::<StakeState>() as u64;
+
+        let mut instructions = vec![system_instruction::create_account(
+            &self.context.payer.pubkey(),
+            &destination.pubkey(),
+            rent.minimum_balance(stake_state_len as usize),
+            stake_state_len,
+            &solana_program::stake::program::id(),
+        )];
+        instructions.append(&mut stake::split(
+            &source,
+            &authorized_staker_withdrawer.pubkey(),
+            lamports.0,
+            &destination.pubkey(),
+        ));
+
+        send_transaction(
+            &mut self.context,
+            &instructions,
+            vec![&destination, authorized_staker_withdrawer],
+        )
+        .await
+        .expect("Failed to split stake.");
+
+        destination.pubkey()
+    }
+
+    /// Redelegate a stake account to a new vote account, outside of Solido.
+    ///
+    /// Mirrors the stake program's `redelegate` instruction, which moves the
+    /// stake into a freshly created account bound to the new vote account,
+    /// leaving the original account to cool down. Returns the new account's
+    /// address.
+    pub async fn redelegate_stake_account(
+        &mut self,
+        source: Pubkey,
+        vote_account: Pubkey,
+        authorized_staker: &Keypair,
+    ) -> Pubkey {
+        use solana_program::stake::instruction as stake;
+        use solana_program::stake::state::StakeState;
+
+        let destination = self.deterministic_keypair.new_keypair();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let stake_state_len = std::mem::size_of::<StakeState>() as u64;
+
+        let mut instructions = vec![system_instruction::create_account(
+            &self.context.payer.pubkey(),
+            &destination.pubkey(),
+            rent.minimum_balance(stake_state_len as usize),
+            stake_state_len,
+            &solana_program::stake::program::id(),
+        )];
+        instructions.append(&mut stake::redelegate(
+            &source,
+            &authorized_staker.pubkey(),
+            &vote_account,
+            &destination.pubkey(),
+        ));
+
+        send_transaction(
+            &mut self.context,
+            &instructions,
+            vec![&destination, authorized_staker],
+        )
+        .await
+        .expect("Failed to redelegate stake.");
+
+        destination.pubkey()
+    }
+
     /// Create a vote account for the given validator.
     pub async fn create_vote_account(
         &mut self,
@@ -600,6 +908,135 @@ impl Context {
         vote_account.pubkey()
     }
 
+    /// Change the commission of a validator's vote account, outside of Solido.
+    ///
+    /// This lets a test simulate a validator raising or lowering its
+    /// commission after it has already been added to Solido, to exercise
+    /// `Criteria::max_commission` enforcement and `max_commission_observed`
+    /// tracking on the next round of performance updates.
+    pub async fn set_vote_account_commission(
+        &mut self,
+        vote_account: Pubkey,
+        withdraw_authority: &Keypair,
+        commission: u8,
+    ) {
+        let instruction =
+            vote_instruction::update_commission(&vote_account, &withdraw_authority.pubkey(), commission);
+        send_transaction(&mut self.context, &[instruction], vec![withdraw_authority])
+            .await
+            .expect("Failed to update vote account commission.");
+    }
+
+    /// Change the authorized voter of a validator's vote account, outside of Solido.
+    ///
+    /// Lets a test simulate a validator rotating its voting key after it has
+    /// already been added to Solido, independently of `node_pubkey` or the
+    /// withdraw authority.
+    pub async fn set_vote_account_authorized_voter(
+        &mut self,
+        vote_account: Pubkey,
+        withdraw_authority: &Keypair,
+        new_authorized_voter: &Keypair,
+    ) {
+        let instruction = vote_instruction::authorize(
+            &vote_account,
+            &withdraw_authority.pubkey(),
+            &new_authorized_voter.pubkey(),
+            VoteAuthorize::Voter,
+        );
+        send_transaction(
+            &mut self.context,
+            &[instruction],
+            vec![withdraw_authority, new_authorized_voter],
+        )
+        .await
+        .expect("Failed to update vote account authorized voter.");
+    }
+
+    /// Change the node identity of a validator's vote account, outside of Solido.
+    ///
+    /// Both the current withdraw authority and the new node key must sign,
+    /// mirroring the real `vote_instruction::update_validator_identity` accounts.
+    pub async fn set_vote_account_node_identity(
+        &mut self,
+        vote_account: Pubkey,
+        withdraw_authority: &Keypair,
+        new_node_key: &Keypair,
+    ) {
+        let instruction = vote_instruction::update_validator_identity(
+            &vote_account,
+            &withdraw_authority.pubkey(),
+            &new_node_key.pubkey(),
+        );
+        send_transaction(
+            &mut self.context,
+            &[instruction],
+            vec![withdraw_authority, new_node_key],
+        )
+        .await
+        .expect("Failed to update vote account node identity.");
+    }
+
+    /// Create an address lookup table seeded with the Solido PDAs, list
+    /// accounts, and the current validator's stake accounts.
+    ///
+    /// This gives tests a ready-made table to pass to
+    /// `send_versioned_transaction`, so instructions close to the legacy
+    /// account limit can still be exercised.
+    pub async fn create_solido_address_lookup_table(&mut self) -> AddressLookupTableAccount {
+        let clock = self.get_clock().await;
+        let payer = self.context.payer.pubkey();
+
+        let (create_instruction, lookup_table_address) = lookup_table_instruction::create_lookup_table(
+            payer,
+            payer,
+            clock.slot.saturating_sub(1),
+        );
+        send_transaction(&mut self.context, &[create_instruction], vec![])
+            .await
+            .expect("Failed to create address lookup table.");
+
+        let mut addresses = vec![
+            self.solido.pubkey(),
+            self.validator_list.pubkey(),
+            self.validator_perf_list.pubkey(),
+            self.maintainer_list.pubkey(),
+            self.reserve_address,
+            self.stake_authority,
+            self.mint_authority,
+        ];
+        if let Some(validator_accounts) = &self.validator {
+            addresses.push(validator_accounts.vote_account);
+            let validators = self.get_solido().await.validators;
+            if let Some(validator) = validators.find(&validator_accounts.vote_account) {
+                for seed in &validator.stake_seeds {
+                    let (stake_address, _) = validator.find_stake_account_address(
+                        &id(),
+                        &self.solido.pubkey(),
+                        seed,
+                        StakeType::Stake,
+                    );
+                    addresses.push(stake_address);
+                }
+            }
+        }
+
+        let extend_instruction = lookup_table_instruction::extend_lookup_table(
+            lookup_table_address,
+            payer,
+            Some(payer),
+            addresses.clone(),
+        );
+        send_transaction(&mut self.context, &[extend_instruction], vec![])
+            .await
+            .expect("Failed to extend address lookup table.");
+
+        AddressLookupTableAccount {
+            key: lookup_table_address,
+            addresses,
+        }
+    }
+
     /// Create an account with a given owner and size.
     pub async fn create_account(&mut self, owner: &Pubkey, size: usize) -> Keypair {
         let account = self.deterministic_keypair.new_keypair();
@@ -1085,6 +1522,156 @@ impl Context {
             .expect("Failed to call Unstake on Solido instance.");
     }
 
+    /// Unstake every active stake account of `validator_vote_account` into
+    /// fresh unstake accounts in a single transaction, and enqueue the
+    /// validator for removal.
+    ///
+    /// This replaces the multi-step chore of unstaking each stake account
+    /// one at a time, waiting for cooldown, and only then enqueueing the
+    /// validator once it is observed to be empty.
+    pub async fn try_decommission_validator(
+        &mut self,
+        validator_vote_account: Pubkey,
+    ) -> transport::Result<()> {
+        let solido = self.get_solido().await;
+        let validator = solido.validators.find(&validator_vote_account).unwrap();
+
+        let source_stake_accounts: Vec<Pubkey> = validator
+            .stake_seeds
+            .into_iter()
+            .map(|seed| {
+                validator
+                    .find_stake_account_address(&id(), &self.solido.pubkey(), seed, StakeType::Stake)
+                    .0
+            })
+            .collect();
+
+        let destination_unstake_accounts: Vec<Pubkey> = (validator.unstake_seeds.end
+            ..validator.unstake_seeds.end + source_stake_accounts.len() as u64)
+            .map(|seed| {
+                validator
+                    .find_stake_account_address(&id(), &self.solido.pubkey(), seed, StakeType::Unstake)
+                    .0
+            })
+            .collect();
+
+        let validator_index = solido.validators.position(&validator_vote_account).unwrap();
+        let maintainer = self.maintainer.as_ref().unwrap();
+        let maintainer_index = solido.maintainers.position(&maintainer.pubkey()).unwrap();
+
+        send_transaction(
+            &mut self.context,
+            &[instruction::decommission_validator(
+                &id(),
+                &instruction::DecommissionValidatorAccountsMeta {
+                    lido: self.solido.pubkey(),
+                    validator_vote_account,
+                    source_stake_accounts,
+                    destination_unstake_accounts,
+                    stake_authority: self.stake_authority,
+                    maintainer: maintainer.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
+                    maintainer_list: self.maintainer_list.pubkey(),
+                },
+                validator_index,
+                maintainer_index,
+            )],
+            vec![self.maintainer.as_ref().unwrap()],
+        )
+        .await
+    }
+
+    /// Decommission the validator, see `try_decommission_validator`.
+    pub async fn decommission_validator(&mut self, validator_vote_account: Pubkey) {
+        self.try_decommission_validator(validator_vote_account)
+            .await
+            .expect("Failed to decommission validator.");
+    }
+
+    /// Move `amount` of active stake from `from_vote_account` to
+    /// `to_vote_account` in a single maintainer transaction.
+    ///
+    /// The decreasing leg splits `amount` off the source validator's first
+    /// stake account into a fresh unstake account, the same way `try_unstake`
+    /// does. The increasing leg simultaneously stakes `amount` from the
+    /// reserve into the destination validator's append stake account, the
+    /// same target resolution `try_stake_deposit` uses for
+    /// `StakeDeposit::Append`. Both legs land in one transaction, so the
+    /// exchange rate stays correct throughout the source's cooldown.
+    pub async fn try_rebalance_stake(
+        &mut self,
+        from_vote_account: Pubkey,
+        to_vote_account: Pubkey,
+        amount: Lamports,
+    ) -> transport::Result<()> {
+        let solido = self.get_solido().await;
+        let from_validator = solido.validators.find(&from_vote_account).unwrap();
+        let to_validator = solido.validators.find(&to_vote_account).unwrap();
+
+        let (source_stake_account, _) = from_validator.find_stake_account_address(
+            &id(),
+            &self.solido.pubkey(),
+            from_validator.stake_seeds.begin,
+            StakeType::Stake,
+        );
+        let (destination_unstake_account, _) = from_validator.find_stake_account_address(
+            &id(),
+            &self.solido.pubkey(),
+            from_validator.unstake_seeds.end,
+            StakeType::Unstake,
+        );
+
+        let (destination_stake_account, _) = to_validator.find_stake_account_address(
+            &id(),
+            &self.solido.pubkey(),
+            to_validator.stake_seeds.end,
+            StakeType::Stake,
+        );
+
+        let from_validator_index = solido.validators.position(&from_vote_account).unwrap();
+        let to_validator_index = solido.validators.position(&to_vote_account).unwrap();
+        let maintainer = self.maintainer.as_ref().unwrap();
+        let maintainer_index = solido.maintainers.position(&maintainer.pubkey()).unwrap();
+
+        send_transaction(
+            &mut self.context,
+            &[instruction::rebalance_stake(
+                &id(),
+                &instruction::RebalanceStakeAccountsMeta {
+                    lido: self.solido.pubkey(),
+                    from_validator_vote_account: from_vote_account,
+                    to_validator_vote_account: to_vote_account,
+                    source_stake_account,
+                    destination_unstake_account,
+                    reserve: self.reserve_address,
+                    destination_stake_account,
+                    stake_authority: self.stake_authority,
+                    maintainer: maintainer.pubkey(),
+                    validator_list: self.validator_list.pubkey(),
+                    maintainer_list: self.maintainer_list.pubkey(),
+                },
+                amount,
+                from_validator_index,
+                to_validator_index,
+                maintainer_index,
+            )],
+            vec![maintainer],
+        )
+        .await
+    }
+
+    /// Rebalance stake between two validators, see `try_rebalance_stake`.
+    pub async fn rebalance_stake(
+        &mut self,
+        from_vote_account: Pubkey,
+        to_vote_account: Pubkey,
+        amount: Lamports,
+    ) {
+        self.try_rebalance_stake(from_vote_account, to_vote_account, amount)
+            .await
+            .expect("Failed to rebalance stake.");
+    }
+
     pub async fn try_change_reward_distribution(
         &mut self,
         new_reward_distribution: &RewardDistribution,
@@ -1241,6 +1828,66 @@ impl Context {
             .expect("Failed to withdraw inactive stake.");
     }
 
+    /// Like `try_update_stake_account_balance`, but refreshes a contiguous
+    /// slice `[start_index, start_index + count)` of the validator list with
+    /// a single `UpdateStakeAccountBalanceBatch` instruction, sharing the
+    /// reserve/mint/fee accounts across the whole batch.
+    pub async fn try_update_stake_account_balance_batch(
+        &mut self,
+        start_index: u32,
+        count: u32,
+    ) -> transport::Result<()> {
+        let solido = self.get_solido().await;
+
+        let mut stake_account_addrs: Vec<Pubkey> = Vec::new();
+        for validator in solido
+            .validators
+            .entries
+            .iter()
+            .skip(start_index as usize)
+            .take(count as usize)
+        {
+            stake_account_addrs.extend(validator.stake_seeds.into_iter().map(|seed| {
+                validator
+                    .find_stake_account_address(&id(), &self.solido.pubkey(), seed, StakeType::Stake)
+                    .0
+            }));
+            stake_account_addrs.extend(validator.unstake_seeds.into_iter().map(|seed| {
+                validator
+                    .find_stake_account_address(&id(), &self.solido.pubkey(), seed, StakeType::Unstake)
+                    .0
+            }));
+        }
+
+        send_transaction(
+            &mut self.context,
+            &[instruction::update_stake_account_balance_batch(
+                &id(),
+                &instruction::UpdateStakeAccountBalanceBatchMeta {
+                    lido: self.solido.pubkey(),
+                    stake_accounts: stake_account_addrs,
+                    reserve: self.reserve_address,
+                    stake_authority: self.stake_authority,
+                    st_sol_mint: self.st_sol_mint,
+                    mint_authority: self.mint_authority,
+                    treasury_st_sol_account: self.treasury_st_sol_account,
+                    developer_st_sol_account: self.developer_st_sol_account,
+                    validator_list: self.validator_list.pubkey(),
+                },
+                start_index,
+                count,
+            )],
+            vec![],
+        )
+        .await
+    }
+
+    pub async fn update_stake_account_balance_batch(&mut self, start_index: u32, count: u32) {
+        self.try_update_stake_account_balance_batch(start_index, count)
+            .await
+            .expect("Failed to update stake account balances in batch.");
+    }
+
     /// Update the commission in the performance readings for the given validator.
     pub async fn try_update_onchain_validator_perf(
         &mut self,
@@ -1272,11 +1919,17 @@ impl Context {
     }
 
     /// Update the perf account for the given validator with the given reading.
+    ///
+    /// `new_data_center_stake_concentration` and `new_in_superminority` feed
+    /// the topology-aware criteria: pass `0` and `false` for tests that do
+    /// not care about data center concentration or superminority exclusion.
     pub async fn try_update_offchain_validator_perf(
         &mut self,
         validator_vote_account: Pubkey,
         new_block_production_rate: u64,
         new_vote_success_rate: u64,
+        new_data_center_stake_concentration: u64,
+        new_in_superminority: bool,
     ) -> transport::Result<()> {
         send_transaction(
             &mut self.context,
@@ -1284,6 +1937,8 @@ impl Context {
                 &id(),
                 new_block_production_rate,
                 new_vote_success_rate,
+                new_data_center_stake_concentration,
+                new_in_superminority,
                 &instruction::UpdateOffchainValidatorPerfAccountsMeta {
                     lido: self.solido.pubkey(),
                     validator_vote_account_to_update: validator_vote_account,
@@ -1301,16 +1956,72 @@ impl Context {
         validator_vote_account: Pubkey,
         new_block_production_rate: u64,
         new_vote_success_rate: u64,
+        new_data_center_stake_concentration: u64,
+        new_in_superminority: bool,
     ) {
         self.try_update_offchain_validator_perf(
             validator_vote_account,
             new_block_production_rate,
             new_vote_success_rate,
+            new_data_center_stake_concentration,
+            new_in_superminority,
         )
         .await
         .expect("Validator performance metrics could always be updated");
     }
 
+    /// Drive a validator's performance metrics from a chosen vote-credit
+    /// trajectory, instead of advancing through many real epochs.
+    ///
+    /// Injects `epoch_credits` into the validator's vote account with
+    /// `set_vote_account` (preserving its existing commission and node
+    /// identity), then computes a rate from the credits earned so far this
+    /// epoch the same way the maintainer CLI's
+    /// `command_collect_validator_performance` computes `vote_success_rate`,
+    /// and records it as both `block_production_rate` and `vote_success_rate`
+    /// (the CLI takes block production from leader-slot data instead),
+    /// with `0`/`false` topology readings, through
+    /// `update_offchain_validator_perf` so `get_solido().validator_perfs`
+    /// reflects it.
+    pub async fn simulate_validator_performance(
+        &mut self,
+        vote_account: Pubkey,
+        epoch_credits: &[(u64, u64, u64)],
+    ) {
+        let existing = self
+            .get_vote_account(vote_account)
+            .await
+            .expect("Vote account does not exist or failed to deserialize.");
+
+        self.set_vote_account(
+            vote_account,
+            existing.commission,
+            existing.node_pubkey,
+            epoch_credits.to_vec(),
+        )
+        .await;
+
+        let clock = self.get_clock().await;
+        let epoch_schedule = self.context.genesis_config().epoch_schedule;
+        let slots_elapsed = clock
+            .slot
+            .saturating_sub(epoch_schedule.get_first_slot_in_epoch(clock.epoch))
+            .max(1);
+
+        let (credits_start, credits_end) = epoch_credits
+            .iter()
+            .find(|(epoch, _, _)| *epoch == clock.epoch)
+            .map(|(_, credits, prev_credits)| (*prev_credits, *credits))
+            .unwrap_or((0, 0));
+        let credits_earned = credits_end.saturating_sub(credits_start);
+
+        let rate = (((credits_earned.min(slots_elapsed) as u128) * (u64::MAX as u128))
+            / slots_elapsed as u128) as u64;
+
+        self.update_offchain_validator_perf(vote_account, rate, rate, 0, false)
+            .await;
+    }
+
     pub async fn try_get_account(&mut self, address: Pubkey) -> Option<Account> {
         self.context
             .banks_client
@@ -1334,6 +2045,117 @@ impl Context {
             .unwrap_or_else(|| panic!("Account {} does not exist.", address))
     }
 
+    /// Write a fully-formed account directly into the bank, bypassing the
+    /// program entirely.
+    ///
+    /// This is much faster than replaying the transactions that would
+    /// normally produce this account state, and it can express mid-epoch
+    /// states, like a stake account frozen in partial warmup, that the
+    /// on-chain instructions alone cannot reach.
+    pub fn set_account(&mut self, address: Pubkey, owner: Pubkey, lamports: u64, data: Vec<u8>) {
+        let account = Account {
+            lamports,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context
+            .set_account(&address, &AccountSharedData::from(account));
+    }
+
+    /// Overwrite the validator list account with exactly `entries`, without
+    /// going through `add_validator`.
+    pub async fn set_validator_list(&mut self, entries: Vec<Validator>) {
+        let mut list = AccountList::<Validator>::new_default(entries.len().max(1));
+        list.entries = entries;
+        let size = AccountList::<Validator>::required_bytes(list.entries.len());
+        let mut data = vec![0; size];
+        BorshSerialize::serialize(&list, &mut data.as_mut_slice())
+            .expect("Failed to serialize validator list.");
+        let rent = self.get_rent().await;
+        self.set_account(
+            self.validator_list.pubkey(),
+            id(),
+            rent.minimum_balance(size),
+            data,
+        );
+    }
+
+    /// Overwrite the maintainer list account with exactly `entries`, without
+    /// going through `add_maintainer`.
+    pub async fn set_maintainer_list(&mut self, entries: Vec<Maintainer>) {
+        let mut list = AccountList::<Maintainer>::new_default(entries.len().max(1));
+        list.entries = entries;
+        let size = AccountList::<Maintainer>::required_bytes(list.entries.len());
+        let mut data = vec![0; size];
+        BorshSerialize::serialize(&list, &mut data.as_mut_slice())
+            .expect("Failed to serialize maintainer list.");
+        let rent = self.get_rent().await;
+        self.set_account(
+            self.maintainer_list.pubkey(),
+            id(),
+            rent.minimum_balance(size),
+            data,
+        );
+    }
+
+    /// Overwrite an existing stake account's delegation state directly,
+    /// without sending a `delegate`/`deactivate` instruction.
+    ///
+    /// This is the only way to put a stake account into a state that only
+    /// arises mid-epoch, such as partially through warmup.
+    pub async fn set_stake_account_state(&mut self, stake_account: Pubkey, stake: Stake) {
+        use solana_program::stake::state::StakeState;
+
+        let mut account = self.get_account(stake_account).await;
+        let mut state: StakeState =
+            bincode::deserialize(&account.data).expect("Failed to parse stake account state.");
+        match &mut state {
+            StakeState::Stake(_meta, existing_stake) => *existing_stake = stake,
+            _ => panic!("Can only overwrite the stake of an already-delegated stake account."),
+        }
+        account.data = bincode::serialize(&state).expect("Failed to serialize stake account state.");
+        self.set_account(stake_account, account.owner, account.lamports, account.data);
+    }
+
+    /// Inject a hand-crafted vote account into the bank, without driving the
+    /// real vote program through many epochs to reach the state under test.
+    ///
+    /// `epoch_credits` is `(epoch, credits, prev_credits)` triples, matching
+    /// `VoteState::epoch_credits` directly, so tests can pin an exact
+    /// block-production/vote-credit trajectory for `Criteria` enforcement.
+    pub async fn set_vote_account(
+        &mut self,
+        address: Pubkey,
+        commission: u8,
+        node_pubkey: Pubkey,
+        epoch_credits: Vec<(u64, u64, u64)>,
+    ) {
+        let mut vote_state = VoteState::new(
+            &VoteInit {
+                node_pubkey,
+                authorized_voter: node_pubkey,
+                authorized_withdrawer: node_pubkey,
+                commission,
+            },
+            &self.get_clock().await,
+        );
+        vote_state.epoch_credits = epoch_credits;
+
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(&VoteStateVersions::Current(Box::new(vote_state)), &mut data)
+            .expect("Failed to serialize VoteState.");
+
+        let rent = self.get_rent().await;
+        self.set_account(
+            address,
+            solana_vote_program::id(),
+            rent.minimum_balance(VoteState::size_of()),
+            data,
+        );
+    }
+
     pub async fn get_account_list<T>(&mut self, address: Pubkey) -> Option<AccountList<T>>
     where
         T: ListEntry + Clone + Default + BorshSerialize,
@@ -1485,6 +2307,81 @@ impl Context {
         StakeAccount::from_delegated_account(stake_balance, &stake, &clock, &stake_history, seed)
     }
 
+    /// Assert that the on-chain state is internally consistent.
+    ///
+    /// Checks that the total SOL under management (the reserve balance, plus
+    /// the sum of every validator's stake and unstake account balances)
+    /// matches the stSOL supply converted through `Lido::exchange_rate`,
+    /// within a small rounding tolerance, and that every validator's cached
+    /// `stake_accounts_balance` matches the sum of its on-chain stake and
+    /// unstake accounts. Intended to be called after maintenance operations
+    /// in tests, to catch stake-tracking drift or double-counting bugs
+    /// before they compound across the deposit/stake/unstake/withdraw flows.
+    pub async fn assert_solido_invariants(&mut self) {
+        let solido = self.get_solido().await;
+        let reserve_balance = self.get_sol_balance(self.reserve_address).await;
+
+        let mint_account = self.get_account(self.st_sol_mint).await;
+        let mint = spl_token::state::Mint::unpack_from_slice(mint_account.data.as_slice())
+            .expect("Failed to deserialize stSOL mint.");
+        let st_sol_supply = StLamports(mint.supply);
+
+        let mut total_stake_balance = Lamports(0);
+        for validator in &solido.validators.entries {
+            let mut validator_unstake_balance = Lamports(0);
+            for seed in validator.unstake_seeds {
+                let unstake_account = self.get_unstake_account_from_seed(validator, seed).await;
+                validator_unstake_balance = (validator_unstake_balance + unstake_account.balance)
+                    .expect("Unstake balance overflow.");
+            }
+
+            let mut validator_stake_balance = validator_unstake_balance;
+            for seed in validator.stake_seeds {
+                let stake_account = self.get_stake_account_from_seed(validator, seed).await;
+                validator_stake_balance = (validator_stake_balance + stake_account.balance)
+                    .expect("Stake balance overflow.");
+            }
+
+            assert_eq!(
+                validator_stake_balance, validator.stake_accounts_balance,
+                "Validator {}: stake_accounts_balance does not match its on-chain stake accounts.",
+                validator.pubkey(),
+            );
+            assert_eq!(
+                validator_unstake_balance, validator.unstake_accounts_balance,
+                "Validator {}: unstake_accounts_balance does not match its on-chain unstake accounts.",
+                validator.pubkey(),
+            );
+
+            total_stake_balance = (total_stake_balance + validator_stake_balance)
+                .expect("Total stake balance overflow.");
+        }
+
+        let total_sol_under_management = (reserve_balance + total_stake_balance)
+            .expect("Total SOL under management overflow.");
+        let expected_sol_under_management = solido
+            .lido
+            .exchange_rate
+            .exchange_st_sol(st_sol_supply)
+            .unwrap_or(Lamports(0));
+
+        // The exchange rate is only a snapshot taken at the last
+        // `UpdateExchangeRate`, so rewards accrued since then can nudge the
+        // actual balance away from the converted stSOL supply by a few
+        // lamports; allow a small tolerance for that.
+        const TOLERANCE_LAMPORTS: u64 = 10;
+        assert!(
+            total_sol_under_management
+                .0
+                .abs_diff(expected_sol_under_management.0)
+                <= TOLERANCE_LAMPORTS,
+            "Total SOL under management ({}) does not match the stSOL supply \
+            converted through the exchange rate ({}).",
+            total_sol_under_management,
+            expected_sol_under_management,
+        );
+    }
+
     pub async fn get_vote_account(
         &mut self,
         vote_account: Pubkey,
@@ -1493,6 +2390,41 @@ impl Context {
         VoteState::deserialize(&vote_acc.data)
     }
 
+    /// Simulate `instructions` as a transaction signed by `additional_signers`,
+    /// without committing it, and return the program log messages it produced.
+    ///
+    /// `assert_solido_error!`/`assert_error_code!` only match on the
+    /// `InstructionError::Custom` code a transaction failed with; this lets
+    /// tests additionally assert on the human-readable `msg!` output a
+    /// handler emits, via `assert_solido_log!`.
+    pub async fn simulate_transaction_logs(
+        &mut self,
+        instructions: &[Instruction],
+        additional_signers: Vec<&Keypair>,
+    ) -> Vec<String> {
+        self.context.last_blockhash = self
+            .context
+            .banks_client
+            .get_new_latest_blockhash(&self.context.last_blockhash)
+            .await
+            .expect("Failed to get a new blockhash.");
+
+        let mut transaction =
+            Transaction::new_with_payer(instructions, Some(&self.context.payer.pubkey()));
+        let mut signers = additional_signers;
+        signers.push(&self.context.payer);
+        transaction.sign(&signers, self.context.last_blockhash);
+
+        self.context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .expect("Failed to simulate transaction.")
+            .simulation_details
+            .map(|details| details.logs)
+            .unwrap_or_default()
+    }
+
     pub async fn try_set_max_commission_percentage(
         &mut self,
         max_commission: u8,
@@ -1666,3 +2598,22 @@ macro_rules! assert_error_code {
         }
     };
 }
+
+/// Like `assert_solido_error!`, but asserts on the program's log output
+/// instead of an `InstructionError::Custom` code. Use this to pin behavior
+/// like "rejected because commission exceeds max" distinctly from other
+/// paths that happen to return the same custom error code, or to verify
+/// informational logs on the happy path.
+///
+/// `logs` should come from `Context::simulate_transaction_logs`.
+#[macro_export]
+macro_rules! assert_solido_log {
+    ($logs:expr, $substring:expr $(, /* Accept an optional trailing comma. */)?) => {
+        assert!(
+            $logs.iter().any(|line| line.contains($substring)),
+            "Expected a log line containing {:?}, got: {:#?}",
+            $substring,
+            $logs,
+        );
+    };
+}